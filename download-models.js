@@ -19,11 +19,12 @@ import { fileURLToPath } from 'url';
 import { pipeline } from 'stream/promises';
 import { spawn } from 'child_process';
 import zlib from 'zlib';
+import crypto from 'crypto';
 
 const __dirname = path.dirname(fileURLToPath(import.meta.url));
 
 // Configuration
-const MODELS_JSON_URL = 'https://storage.googleapis.com/moz-fx-translations-data--303e-prod-translations-data/db/models.json';
+const MODELS_JSON_URL = process.env.MODELS_JSON_URL || 'https://storage.googleapis.com/moz-fx-translations-data--303e-prod-translations-data/db/models.json';
 const OUTPUT_DIR = process.env.MODEL_DIR || './models';
 
 // CLI args
@@ -54,7 +55,8 @@ async function downloadFile(url, destPath, retries = 3) {
             let downloadedSize = 0;
             let fileSize = 0;
 
-            return new Promise((resolve, reject) => {
+            // curl -C - resumes a partial file with an HTTP Range request
+            return await new Promise((resolve, reject) => {
                 const proc = spawn('curl', ['-fsSL', '-C', '-', '-o', destPath, url], {
                     stdio: 'ignore'
                 });
@@ -97,6 +99,24 @@ async function decompressFile(filePath) {
     }
 }
 
+// Verify a decompressed file against the size/SHA-256 listed in models.json.
+// A corrupt file is removed so the next run downloads it again.
+async function verifyChecksum(filePath, fileInfo) {
+    if (!fileInfo?.uncompressedHash) return;
+
+    const fileName = path.basename(filePath);
+    const hash = crypto.createHash('sha256');
+    await pipeline(fss.createReadStream(filePath), hash);
+    const actual = hash.digest('hex');
+    const { size } = await fs.stat(filePath);
+
+    if (actual !== fileInfo.uncompressedHash || (fileInfo.uncompressedSize && size !== fileInfo.uncompressedSize)) {
+        await fs.unlink(filePath);
+        throw new Error(`Checksum mismatch for ${fileName} (expected ${fileInfo.uncompressedHash}, got ${actual})`);
+    }
+    console.log(`  Verified: ${fileName}`);
+}
+
 // Fetch and parse models.json
 async function getModelUrls(saveToFile = true) {
     console.log('Fetching models metadata...');
@@ -224,6 +244,7 @@ async function downloadModel(modelKey, baseUrl, modelData) {
         const tempPath = destPath + '.gz';
         await downloadFile(`${baseUrl}/${filePath}`, tempPath);
         await decompressFile(tempPath);
+        await verifyChecksum(destPath, files.lexicalShortlist);
     }
 
     // Download model with original filename
@@ -234,6 +255,7 @@ async function downloadModel(modelKey, baseUrl, modelData) {
         const tempPath = destPath + '.gz';
         await downloadFile(`${baseUrl}/${filePath}`, tempPath);
         await decompressFile(tempPath);
+        await verifyChecksum(destPath, files.model);
     }
 
     // Download vocabularies with original filenames
@@ -245,9 +267,11 @@ async function downloadModel(modelKey, baseUrl, modelData) {
 
         await downloadFile(`${baseUrl}/${srcPath}`, srcDest + '.gz');
         await decompressFile(srcDest + '.gz');
+        await verifyChecksum(srcDest, files.srcVocab);
 
         await downloadFile(`${baseUrl}/${trgPath}`, trgDest + '.gz');
         await decompressFile(trgDest + '.gz');
+        await verifyChecksum(trgDest, files.trgVocab);
     } else if (files.vocab) {
         const filePath = files.vocab.path;
         const origName = path.basename(filePath).replace(/\.gz$/, '');
@@ -255,6 +279,7 @@ async function downloadModel(modelKey, baseUrl, modelData) {
         const tempPath = destPath + '.gz';
         await downloadFile(`${baseUrl}/${filePath}`, tempPath);
         await decompressFile(tempPath);
+        await verifyChecksum(destPath, files.vocab);
 
         // Copy vocab as both src and trg
         await fs.copyFile(destPath, path.join(modelDir, 'srcvocab.spm'));
@@ -324,6 +349,7 @@ Examples:
 
 Environment:
   MODEL_DIR         Override default output directory
+  MODELS_JSON_URL   Override the models.json registry URL (e.g. a mirror)
 `);
         process.exit(0);
     }
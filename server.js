@@ -20,20 +20,132 @@ import path from 'path';
 import os from 'os';
 import { fileURLToPath } from 'url';
 import vm from 'vm';
-import { franc } from 'franc';
+import { spawn } from 'child_process';
+import crypto from 'crypto';
+import zlib from 'zlib';
+import { franc, francAll } from 'franc';
+import { pinyin } from 'pinyin-pro';
 import swaggerUi from 'swagger-ui-express';
 
 const __dirname = path.dirname(fileURLToPath(import.meta.url));
 
-// Configuration
+// ============== Configuration ==============
+
+// Environment parsing: every setting is read here, once. Malformed values log a
+// warning and fall back to the default instead of being silently misread.
+const configWarnings = []; // reported again by --check-config
+
+function configWarn(message) {
+    configWarnings.push(message);
+    console.warn(`[Server] ${message}`);
+}
+
+function envWarn(name, value, expected, fallback) {
+    configWarn(`Invalid ${name}=${JSON.stringify(value)} (expected ${expected}), using ${JSON.stringify(fallback)}`);
+    return fallback;
+}
+
+function envString(name, fallback = '') {
+    return process.env[name] || fallback;
+}
+
+function envInt(name, fallback, { min = -Infinity, max = Infinity } = {}) {
+    const value = process.env[name];
+    if (value === undefined || value === '') return fallback;
+    const parsed = Number(value);
+    if (!Number.isInteger(parsed) || parsed < min || parsed > max) {
+        return envWarn(name, value, `an integer in [${min}, ${max}]`, fallback);
+    }
+    return parsed;
+}
+
+function envFloat(name, fallback, { min = -Infinity, max = Infinity } = {}) {
+    const value = process.env[name];
+    if (value === undefined || value === '') return fallback;
+    const parsed = Number(value);
+    if (!Number.isFinite(parsed) || parsed < min || parsed > max) {
+        return envWarn(name, value, `a number in [${min}, ${max}]`, fallback);
+    }
+    return parsed;
+}
+
+function envBool(name, fallback = false) {
+    const value = process.env[name];
+    if (value === undefined || value === '') return fallback;
+    if (['1', 'true', 'yes', 'on'].includes(value.toLowerCase())) return true;
+    if (['0', 'false', 'no', 'off'].includes(value.toLowerCase())) return false;
+    return envWarn(name, value, '1/0 or true/false', fallback);
+}
+
+function envEnum(name, fallback, allowed) {
+    const value = process.env[name];
+    if (value === undefined || value === '') return fallback;
+    if (!allowed.includes(value)) return envWarn(name, value, `one of ${allowed.join(', ')}`, fallback);
+    return value;
+}
+
+// A template with `token` exactly once, between a non-blank prefix and suffix (so the filled-in
+// text can't be confused with ordinary numbers in the surrounding text)
+function envTemplate(name, fallback, token) {
+    const value = process.env[name];
+    if (value === undefined || value === '') return fallback;
+    const parts = value.split(token);
+    if (parts.length !== 2 || !parts[0].trim() || !parts[1].trim()) {
+        return envWarn(name, value, `a template containing ${token} exactly once, with text on both sides`, fallback);
+    }
+    return value;
+}
+
+function envList(name, fallback = []) {
+    if (process.env[name] === undefined) return fallback;
+    return process.env[name].split(',').map(v => v.trim()).filter(Boolean);
+}
+
 const CONFIG = {
-    PORT: parseInt(process.env.PORT || '3000', 10),
-    IP: process.env.IP || '127.0.0.1',
-    MODELS_DIR: process.env.MODELS_DIR || './models',
-    API_KEY: process.env.API_KEY || '',
-    RUST_LOG: process.env.RUST_LOG || 'info',
-    WASM_PATH: process.env.WASM_PATH || path.join(__dirname, 'wasm', 'bergamot-translator.wasm'),
-    JS_PATH: process.env.JS_PATH || path.join(__dirname, 'wasm', 'bergamot-translator.js'),
+    PORT: envInt('PORT', 3000, { min: 0, max: 65535 }),
+    IP: envString('IP', '127.0.0.1'),
+    MODELS_DIR: envString('MODELS_DIR', './models'),
+    ZIP_CACHE_DIR: envString('ZIP_CACHE_DIR', path.join(os.tmpdir(), 'linguaspark-models')),
+    API_KEY: envString('API_KEY'),
+    API_KEY_FILE: envString('API_KEY_FILE'), // secret mount holding the key; overrides API_KEY
+    API_KEYS_FILE: envString('API_KEYS_FILE'), // one accepted key per line; overrides API_KEY
+    API_KEY_HEADER: envString('API_KEY_HEADER').toLowerCase(),
+    RUST_LOG: envString('RUST_LOG', 'info'),
+    WASM_PATH: envString('WASM_PATH', path.join(__dirname, 'wasm', 'bergamot-translator.wasm')),
+    JS_PATH: envString('JS_PATH', path.join(__dirname, 'wasm', 'bergamot-translator.js')),
+    MAX_OUTPUT_RATIO: envFloat('MAX_OUTPUT_RATIO', 0, { min: 0 }), // 0 = disabled
+    OUTPUT_GUARD_ACTION: envEnum('OUTPUT_GUARD_ACTION', 'flag', ['flag', 'truncate', 'reject']),
+    EXPECTED_LANGUAGES: envList('EXPECTED_LANGUAGES'),
+    DETECT_LOADED_SOURCES_ONLY: envBool('DETECT_LOADED_SOURCES_ONLY'),
+    // API routes reachable without API_KEY; a trailing * matches a prefix
+    PUBLIC_PATHS: envList('PUBLIC_PATHS', ['/health', '/models', '/translate/options']),
+    DETECT_CACHE_SIZE: envInt('DETECT_CACHE_SIZE', 1000, { min: 0 }),
+    DETECT_MIN_CHARS: envInt('DETECT_MIN_CHARS', 10, { min: 0 }),
+    DEFAULT_SOURCE_LANG: envString('DEFAULT_SOURCE_LANG'),
+    REQUIRED_PAIRS: envList('REQUIRED_PAIRS').map(normalizePairName),
+    SKIP_WHEN_DETECTED_TARGET: envBool('SKIP_WHEN_DETECTED_TARGET'),
+    SKIP_DETECTED_TARGET_CONFIDENCE: envFloat('SKIP_DETECTED_TARGET_CONFIDENCE', 0.8, { min: 0, max: 1 }),
+    REQUEST_LOG_SIZE: envInt('REQUEST_LOG_SIZE', 200, { min: 0 }),
+    PAIR_RATE_LIMITS: parsePairRateLimits(envString('PAIR_RATE_LIMITS')), // "enzh:100,jpen:20" per minute
+    PAIR_OPTIONS: parsePairOptions(envString('PAIR_OPTIONS')), // "enzh:beam=4,normalize=0.6;jpen:beam=2"
+    MODEL_LOAD_CONCURRENCY: envInt('MODEL_LOAD_CONCURRENCY', 2, { min: 1 }),
+    MODELS_MANIFEST: envString('MODELS_MANIFEST'), // JSON list of models to register instead of scanning MODELS_DIR
+    BERGAMOT_CONFIG_FILE: envString('BERGAMOT_CONFIG_FILE'),
+    MAX_BATCH_SIZE: envInt('MAX_BATCH_SIZE', 1000, { min: 1 }),
+    MAX_BATCH_CHARS: envInt('MAX_BATCH_CHARS', 100000, { min: 1 }),
+    VERIFY_DETERMINISM: envBool('VERIFY_DETERMINISM'),
+    MODEL_CACHE_BUDGET: envInt('MODEL_CACHE_BUDGET', 0, { min: 0 }), // models with cached files, 0 = unlimited
+    PLACEHOLDER_FORMAT: envTemplate('PLACEHOLDER_FORMAT', '⟦{n}⟧', '{n}'),
+    SERVE_UI: envBool('SERVE_UI'),
+    TRUST_PROXY_HEADERS: envBool('TRUST_PROXY_HEADERS'),
+    SLOW_REQUEST_MS: envInt('SLOW_REQUEST_MS', 5000, { min: 0 }), // 0 = disabled
+    GLOSSARY_FILE: envString('GLOSSARY_FILE'), // JSON { "source term": "target term" } applied to /translate
+    POST_PROCESS_CMD: envString('POST_PROCESS_CMD'), // shell command, translation on stdin -> stdout
+    POST_PROCESS_TIMEOUT_MS: envInt('POST_PROCESS_TIMEOUT_MS', 5000, { min: 1 }),
+    MAX_PIVOT_HOPS: envInt('MAX_PIVOT_HOPS', 1, { min: 0 }), // intermediate languages, 0 = direct only
+    STARTUP_SELFTEST: envBool('STARTUP_SELFTEST'),
+    SIGN_RESPONSES: envBool('SIGN_RESPONSES'), // X-Signature HMAC on /translate, /translate/document, /translate/diff
+    RESPONSE_HMAC_KEY: envString('RESPONSE_HMAC_KEY'),
 };
 
 // Load bergamot-translator.js and WASM binary (will create instances per model)
@@ -92,8 +204,11 @@ async function createBergamotInstance() {
 
 // Express app
 const app = express();
+// Behind a trusted reverse proxy, req.ip is the leftmost X-Forwarded-For entry instead of the proxy
+if (CONFIG.TRUST_PROXY_HEADERS) app.set('trust proxy', true);
 app.use(cors());
 app.use(express.json());
+app.use(recordRequest);
 
 // Load OpenAPI spec for Swagger UI
 let openapiSpec;
@@ -108,8 +223,10 @@ try {
     };
 }
 
-// Serve static files for Web UI
-app.use(express.static(path.join(__dirname, 'public')));
+// Serve static files for Web UI (opt-in with SERVE_UI=1; API-only by default, so / 404s)
+if (CONFIG.SERVE_UI) {
+    app.use(express.static(path.join(__dirname, 'public')));
+}
 
 // Swagger UI at /docs/
 app.use('/docs', swaggerUi.serve, swaggerUi.setup(openapiSpec, {
@@ -122,9 +239,13 @@ app.get('/openapi.json', (req, res) => {
     res.json(openapiSpec);
 });
 
+// Everything registered after this point requires API_KEY unless listed in PUBLIC_PATHS
+app.use(checkAuth);
+
 // State
 let activeModel = null; // Currently loaded model (only one at a time due to WASM limitation)
 const availableModels = new Map(); // key: "from-to", value: { dir, from, to, buffers: null }
+const modelAliases = new Map(); // key: MODELS_MANIFEST alias ("en-zh_Hans"), value: the registry key it serves
 const loadingLocks = new Map(); // key: "from-to", value: Promise (prevents duplicate loads)
 const langCodeMap = {
     // Chinese names
@@ -171,6 +292,7 @@ const translationLog = {
             to: entry.to,
             source: entry.source?.substring(0, 500) || '', // Truncate long texts
             translated: entry.translated?.substring(0, 500) || '',
+            truncated: (entry.source?.length || 0) > 500 || (entry.translated?.length || 0) > 500,
         });
         // Keep size limited
         if (this.entries.length > this.maxSize) {
@@ -210,12 +332,190 @@ function logTranslation(from, to, source, translated) {
     translationLog.add({ from, to, source, translated });
 }
 
+// ============== Request Log (last N requests, metadata only) ==============
+
+const requestLog = {
+    entries: [], // Ring buffer: { timestamp, method, path, from, to, chars, status, latencyMs }
+    next: 0,
+
+    add(entry) {
+        if (CONFIG.REQUEST_LOG_SIZE <= 0) return;
+        this.entries[this.next] = entry;
+        this.next = (this.next + 1) % CONFIG.REQUEST_LOG_SIZE;
+    },
+
+    // Most recent first
+    getRecent(limit) {
+        const ordered = [...this.entries.slice(this.next), ...this.entries.slice(0, this.next)].reverse();
+        return ordered.slice(0, limit);
+    },
+};
+
+// Total characters submitted for translation, across the single and batch request shapes
+function requestCharCount(body) {
+    if (!body || typeof body !== 'object') return 0;
+    const texts = [body.text, body.texts, body.text_list].flat().filter(t => typeof t === 'string');
+    return texts.reduce((sum, t) => sum + t.length, 0);
+}
+
+// Routes that run text through a model; SLOW_REQUEST_MS only reports these (/detect and
+// /translate/segment also take text but never translate it)
+const TRANSLATION_ROUTES = new Set([
+    '/translate', '/translate/document', '/translate/diff', '/translate/ndjson', '/translate/csv',
+    '/translate/kv', '/kiss', '/imme', '/hcfy', '/deeplx', '/translate_mtranserver',
+    '/translate_mtranserver/batch', '/compare',
+]);
+
+let inFlightRequests = 0;
+
+function recordRequest(req, res, next) {
+    const start = process.hrtime.bigint();
+    inFlightRequests++;
+    // 'close' also fires for aborted requests, which never 'finish'
+    res.once('close', () => inFlightRequests--);
+    res.on('finish', () => {
+        const body = req.body || {};
+        const entry = {
+            timestamp: Date.now(),
+            method: req.method,
+            path: req.path,
+            ip: req.ip,
+            from: body.from || body.source_lang || body.source || null,
+            to: body.to || body.target_lang || body.destination?.[0] || null,
+            chars: requestCharCount(body),
+            status: res.statusCode,
+            latencyMs: Number(process.hrtime.bigint() - start) / 1e6,
+        };
+        requestLog.add(entry);
+        // Slow translations: pair and size only, never the text
+        if (CONFIG.SLOW_REQUEST_MS && TRANSLATION_ROUTES.has(entry.path) && entry.chars > 0 && entry.latencyMs > CONFIG.SLOW_REQUEST_MS) {
+            console.warn(`[Server] Slow request: ${entry.method} ${entry.path} ${entry.from || 'auto'}-${entry.to} ${entry.chars} chars took ${Math.round(entry.latencyMs)}ms`);
+        }
+    });
+    next();
+}
+
+// ============== Error Responses ==============
+
+// Error with an HTTP status and a stable machine-readable code for clients to branch on
+class ApiError extends Error {
+    // retryAfter (seconds) marks a transient failure and is sent as Retry-After
+    constructor(status, code, message, details = {}, retryAfter = null) {
+        super(message);
+        this.status = status;
+        this.code = code;
+        this.details = details;
+        this.retryAfter = retryAfter;
+    }
+}
+
+// Send an error as JSON: { error, code, ...details }
+function sendError(res, err) {
+    if (err instanceof ApiError) {
+        if (err.retryAfter !== null) res.set('Retry-After', String(err.retryAfter));
+        return res.status(err.status).json({ error: err.message, code: err.code, ...err.details });
+    }
+    res.status(500).json({ error: err.message, code: 'TRANSLATION_FAILED' });
+}
+
+function badRequest(res, message) {
+    return sendError(res, new ApiError(400, 'INVALID_REQUEST', message));
+}
+
 // ============== Helpers ==============
 
 function normalizePath(p) {
     return path.normalize(p);
 }
 
+// Normalize a pair name from config ("enzh" or "en-zh") to a model key ("en-zh")
+function normalizePairName(name) {
+    return /^[a-z]{4}$/i.test(name) ? `${name.slice(0, 2)}-${name.slice(2)}` : name;
+}
+
+// Parse "enzh:100,jpen:20" into Map { "en-zh" => 100, "jp-en" => 20 }
+function parsePairRateLimits(value) {
+    const limits = new Map();
+    for (const item of value.split(',').map(v => v.trim()).filter(Boolean)) {
+        const [pair, limit] = item.split(':');
+        const perMinute = parseInt(limit, 10);
+        if (!pair || !(perMinute > 0)) {
+            configWarn(`Ignoring invalid PAIR_RATE_LIMITS entry: ${item}`);
+            continue;
+        }
+        limits.set(normalizePairName(pair), perMinute);
+    }
+    return limits;
+}
+
+// Parse "enzh:beam=4,normalize=0.6;jpen:beam=2" into
+// Map { "en-zh" => { "beam-size": "4", normalize: "0.6" }, "jp-en" => { "beam-size": "2" } }
+function parsePairOptions(value) {
+    // Short names for Marian options; anything else is passed through as written
+    const aliases = { beam: 'beam-size', threads: 'cpu-threads', length_factor: 'max-length-factor' };
+    const pairOptions = new Map();
+    for (const item of value.split(';').map(v => v.trim()).filter(Boolean)) {
+        const [pair, list = ''] = item.split(':');
+        const options = {};
+        for (const option of list.split(',').map(v => v.trim()).filter(Boolean)) {
+            const [name, optionValue] = option.split('=').map(v => v?.trim());
+            if (!/^[a-z][\w-]*$/i.test(name) || !/^[\w.+-]+$/.test(optionValue || '')) {
+                configWarn(`Ignoring invalid PAIR_OPTIONS option for ${pair}: ${option}`);
+                continue;
+            }
+            options[aliases[name] || name] = optionValue;
+        }
+        if (!pair || Object.keys(options).length === 0) {
+            configWarn(`Ignoring invalid PAIR_OPTIONS entry: ${item}`);
+            continue;
+        }
+        pairOptions.set(normalizePairName(pair), options);
+    }
+    return pairOptions;
+}
+
+// Sliding one-minute window of request timestamps per rate-limited pair
+const pairRequestTimes = new Map();
+
+// Charge one client request against each pair of a route: (from, to), or (from, pivot..., to)
+// for every leg of a pivot. Handlers call this once per request, before translating; nothing is
+// charged when any leg is over its limit.
+function checkPairRateLimit(...route) {
+    checkPairRateLimits(route.slice(1).map((to, i) => [route[i], to]));
+}
+
+// Charge one request to each [from, to] pair, or to none of them if any would go over its limit
+function checkPairRateLimits(pairs) {
+    const now = Date.now();
+    const charges = new Map();
+    for (const [from, to] of pairs) {
+        const key = modelKey(from, to);
+        if (CONFIG.PAIR_RATE_LIMITS.has(key)) charges.set(key, (charges.get(key) || 0) + 1);
+    }
+    for (const [key, count] of charges) {
+        const limit = CONFIG.PAIR_RATE_LIMITS.get(key);
+        const times = (pairRequestTimes.get(key) || []).filter(t => now - t < 60000);
+        pairRequestTimes.set(key, times);
+        if (times.length + count > limit) {
+            // Until the oldest request in the window expires
+            const retryAfter = Math.max(1, Math.ceil(((times[0] ?? now) + 60000 - now) / 1000));
+            throw new ApiError(429, 'RATE_LIMITED', `Rate limit exceeded for ${key}: ${limit} requests per minute`, {}, retryAfter);
+        }
+    }
+    for (const [key, count] of charges) {
+        for (let i = 0; i < count; i++) pairRequestTimes.get(key).push(now);
+    }
+}
+
+function escapeXml(text) {
+    return String(text)
+        .replace(/[\x00-\x08\x0B\x0C\x0E-\x1F]/g, '') // not allowed in XML 1.0
+        .replace(/&/g, '&amp;')
+        .replace(/</g, '&lt;')
+        .replace(/>/g, '&gt;')
+        .replace(/"/g, '&quot;');
+}
+
 // MTranServer compatible language code normalization
 const languageAliases = {
     'zh': 'zh',
@@ -271,42 +571,712 @@ function langCodeToModelKey(code) {
     return code;
 }
 
-// Check if a language pair needs pivot translation via English
-function needsPivotTranslation(fromLang, toLang) {
+// Shortest chain of loaded pairs from one language to another, as [from, ...intermediates, to],
+// with at most MAX_PIVOT_HOPS intermediates. Breadth-first with a visited set, so no language
+// appears twice; English is tried first among equally short routes. Returns null if unreachable,
+// and [from] (no legs) when both are the same language.
+function findPivotPath(fromLang, toLang, maxHops = CONFIG.MAX_PIVOT_HOPS) {
     // Use model directory keys (zh instead of zh-Hans)
     const fromKey = langCodeToModelKey(fromLang);
     const toKey = langCodeToModelKey(toLang);
-
-    if (fromKey === 'en' || toKey === 'en') {
-        return false;
+    if (fromKey === toKey) return [fromKey];
+
+    const edges = new Map();
+    const pairs = [
+        ...availableModels.values(),
+        // An alias is a pair of its own ("en-zh_Hans"), served by the model it names
+        ...Array.from(modelAliases.keys(), alias => {
+            const [from, to] = alias.split(':')[0].split('-');
+            return { from, to };
+        }),
+    ];
+    for (const { from, to } of pairs) {
+        if (!edges.has(from)) edges.set(from, new Set());
+        edges.get(from).add(to);
+    }
+    const visited = new Set([fromKey]);
+    let frontier = [[fromKey]];
+    for (let depth = 0; depth <= maxHops && frontier.length > 0; depth++) {
+        const next = [];
+        for (const route of frontier) {
+            const targets = [...(edges.get(route.at(-1)) || [])].sort((a, b) => (b === 'en') - (a === 'en'));
+            if (targets.includes(toKey)) return [...route, toKey];
+            for (const lang of targets) {
+                if (visited.has(lang)) continue;
+                visited.add(lang);
+                next.push([...route, lang]);
+            }
+        }
+        frontier = next;
     }
-    const key = `${fromKey}-${toKey}`;
-    return !availableModels.has(key);
+    return null;
+}
+
+// Target fallback chain: `to` given as a list ("yue, else zh") translates into the first target
+// reachable from the source with at most maxHops pivots. Returns null when none is.
+function firstReachableTarget(fromLang, targets, maxHops) {
+    return targets.find(target => normalizeLanguageCode(target) === normalizeLanguageCode(fromLang)
+        || findPivotPath(fromLang, target, maxHops)) ?? null;
+}
+
+function isValidTargetList(to) {
+    return Array.isArray(to) && to.length > 0 && to.every(target => typeof target === 'string' && target);
+}
+
+// Directory name a loaded model was read from, for ?debug=model responses
+function modelDirName(model) {
+    return path.basename(model.dir);
 }
 
-// Translate with pivot (via English if needed)
-async function translateWithPivot(fromLang, toLang, text, isHTML = false) {
+// Translate directly, or through a chain of pivot languages when there is no direct model.
+// `modelsUsed`, when given, collects the directory name of every model that ran (each leg of a pivot)
+async function translateWithPivot(fromLang, toLang, text, isHTML = false, modelsUsed = null) {
     // Same language - no translation needed
     if (fromLang === toLang) {
         return text;
     }
 
-    // Use model directory keys
-    const fromKey = langCodeToModelKey(fromLang);
-    const toKey = langCodeToModelKey(toLang);
+    const route = findPivotPath(fromLang, toLang);
+    if (!route) {
+        throw unsupportedPairError(`${langCodeToModelKey(fromLang)}-${langCodeToModelKey(toLang)}`);
+    }
+    let result = text;
+    for (let i = 1; i < route.length; i++) {
+        const model = await getModel(route[i - 1], route[i]);
+        modelsUsed?.push(modelDirName(model));
+        result = doTranslate(model, result);
+    }
+    return result;
+}
+
+// Sentences of a paragraph as Intl.Segmenter finds them, as { lead, body, trail } with the
+// surrounding whitespace split off; joining lead + body + trail gives back the paragraph.
+// Whitespace-only segments join the neighbouring sentence.
+const sentenceSegmenter = new Intl.Segmenter(undefined, { granularity: 'sentence' });
+
+function splitSentences(text) {
+    const sentences = [];
+    let pending = '';
+    for (const { segment } of sentenceSegmenter.segment(text)) {
+        const [, lead, body, trail] = segment.match(/^(\s*)([\s\S]*?)(\s*)$/);
+        if (body) {
+            sentences.push({ lead: pending + lead, body, trail });
+            pending = '';
+        } else if (sentences.length > 0) {
+            sentences.at(-1).trail += segment;
+        } else {
+            pending += segment;
+        }
+    }
+    return sentences;
+}
+
+// Split a document into paragraphs (one or more blank lines, LF or CRLF),
+// keeping the separators verbatim.
+// Returns { chunks, separators } where chunks[i] is followed by separators[i].
+function splitDocument(text) {
+    const parts = text.split(/(\r?\n[ \t]*\r?\n\s*)/);
+    const chunks = [];
+    const separators = [];
+    for (let i = 0; i < parts.length; i += 2) {
+        chunks.push(parts[i]);
+        separators.push(parts[i + 1] || '');
+    }
+    return { chunks, separators };
+}
+
+// 400 BATCH_TOO_LARGE when a batch exceeds MAX_BATCH_SIZE segments or MAX_BATCH_CHARS
+// characters in total, or null when it is within limits
+function batchLimitError(texts) {
+    const list = [texts].flat();
+    const segments = list.length;
+    const chars = list.reduce((sum, t) => sum + (typeof t === 'string' ? t.length : 0), 0);
+    if (segments <= CONFIG.MAX_BATCH_SIZE && chars <= CONFIG.MAX_BATCH_CHARS) return null;
+    return new ApiError(400, 'BATCH_TOO_LARGE',
+        `Batch too large: received ${segments} segments / ${chars} chars (limits: ${CONFIG.MAX_BATCH_SIZE} segments / ${CONFIG.MAX_BATCH_CHARS} chars)`,
+        { max_segments: CONFIG.MAX_BATCH_SIZE, max_chars: CONFIG.MAX_BATCH_CHARS, segments, chars });
+}
+
+// Validate an optional deadline_ms (latency budget for document/batch endpoints).
+// Returns the absolute deadline timestamp, Infinity when unset, or null when invalid.
+function parseDeadline(deadlineMs, start) {
+    if (deadlineMs === undefined) return Infinity;
+    if (typeof deadlineMs !== 'number' || !(deadlineMs > 0)) return null;
+    return start + deadlineMs;
+}
+
+// Segment failure policy for batch/document endpoints: 'abort' fails the whole
+// request, 'continue' passes the failing segment through and reports it
+const ON_ERROR_MODES = ['abort', 'continue'];
+
+// Translate a chunk, leaving its leading/trailing whitespace untouched.
+// Returns { text, guard } with the output guard result for the chunk.
+function translateChunk(model, chunk, options = {}) {
+    const match = chunk.match(/^(\s*)([\s\S]*?)(\s*)$/);
+    if (!match[2]) return { text: chunk, guard: null };
+    const { text, guard } = guardOutput(match[2], translateText(model, match[2], options));
+    return { text: match[1] + text + match[3], guard };
+}
+
+// ============== Placeholder Protection ==============
+
+// URLs, emails, and numbers/currency amounts the model tends to reformat or break
+const ENTITY_PATTERN = /https?:\/\/[^\s<>"']*[^\s<>"'.,;:!?)]|www\.[^\s<>"']*[^\s<>"'.,;:!?)]|[\w.+-]+@[\w-]+(?:\.[\w-]+)+|[$€£¥₹]?\d+(?:[.,]\d+)*%?/g;
+
+// bidi_marks: isolate RTL output (RLI ... PDI) and, inside it, each LTR entity (LRI ... PDI) so
+// URLs, emails and numbers keep their order when the text is embedded in LTR content
+const RTL_LANGUAGES = ['ar', 'he', 'fa', 'ur', 'yi', 'ps', 'sd', 'ckb', 'dv'];
+
+function addBidiMarks(text, lang) {
+    if (!RTL_LANGUAGES.includes(normalizeLanguageCode(lang).split(/[-_]/)[0])) return text;
+    return '\u2067' + text.replace(ENTITY_PATTERN, entity => `\u2066${entity}\u2069`) + '\u2069';
+}
+
+// protect_foreign_script: runs of letters in a script the source language doesn't use (e.g.
+// "iPhone 15" in Japanese) are kept verbatim. Runs may contain digits and single spaces or
+// punctuation between words. Languages not listed here are treated as Latin-script.
+const LANGUAGE_SCRIPTS = {
+    zh: ['Han'], 'zh-Hans': ['Han'], 'zh-Hant': ['Han'],
+    ja: ['Han', 'Hiragana', 'Katakana'],
+    ko: ['Hangul', 'Han'],
+    ru: ['Cyrillic'], uk: ['Cyrillic'], be: ['Cyrillic'], bg: ['Cyrillic'], sr: ['Cyrillic'], mk: ['Cyrillic'], kk: ['Cyrillic'],
+    ar: ['Arabic'], fa: ['Arabic'], ur: ['Arabic'], ps: ['Arabic'],
+    he: ['Hebrew'], yi: ['Hebrew'],
+    el: ['Greek'],
+    th: ['Thai'],
+    hi: ['Devanagari'], mr: ['Devanagari'], ne: ['Devanagari'],
+};
+
+function foreignScriptPattern(sourceLang) {
+    const scripts = LANGUAGE_SCRIPTS[normalizeLanguageCode(sourceLang)] || ['Latin'];
+    // Any letter outside the source scripts (the prolonged sound mark ー is Common, not Katakana)
+    const letter = `[^\\P{L}${scripts.map(script => `\\p{Script=${script}}`).join('')}ー]`;
+    const letterOrDigit = `(?:${letter}|\\p{N})`;
+    return `${letter}${letterOrDigit}*(?:[ '’.&-]${letterOrDigit}+)*`;
+}
+
+// i18n format placeholders: {{name}}, {0} / {name}, and printf-style %s, %d, %1$s, %.2f
+const FORMAT_PLACEHOLDER_PATTERN = /\{\{\s*[\w.]+\s*\}\}|\{\w*\}|%(?:\d+\$)?[-+#0]*\d*(?:\.\d+)?[sdifuxXeEgGc@]/g;
+
+// Format placeholders whose count differs between source and translation (lost or duplicated)
+function placeholderMismatches(source, translated) {
+    const count = (text) => {
+        const counts = new Map();
+        for (const token of text.match(FORMAT_PLACEHOLDER_PATTERN) || []) counts.set(token, (counts.get(token) || 0) + 1);
+        return counts;
+    };
+    const expected = count(source);
+    const found = count(translated);
+    return [...new Set([...expected.keys(), ...found.keys()])]
+        .filter(token => expected.get(token) !== found.get(token))
+        .map(token => ({ placeholder: token, expected: expected.get(token) || 0, found: found.get(token) || 0 }));
+}
+
+function escapeRegExp(text) {
+    return text.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+}
+
+// Placeholder text from PLACEHOLDER_FORMAT ("⟦{n}⟧" by default); formats survive models differently
+const [PLACEHOLDER_PREFIX, PLACEHOLDER_SUFFIX] = CONFIG.PLACEHOLDER_FORMAT.split('{n}');
+const PLACEHOLDER_RESTORE_PATTERN = new RegExp(`${escapeRegExp(PLACEHOLDER_PREFIX.trim())}\\s*(\\d+)\\s*${escapeRegExp(PLACEHOLDER_SUFFIX.trim())}`, 'g');
+
+function placeholder(n) {
+    return `${PLACEHOLDER_PREFIX}${n}${PLACEHOLDER_SUFFIX}`;
+}
+
+// Replace every match of `pattern` with a numbered placeholder the model leaves alone
+function protectTokens(text, pattern) {
+    const tokens = [];
+    const protectedText = text.replace(pattern, (match) => {
+        tokens.push(match);
+        return placeholder(tokens.length - 1);
+    });
+    return { text: protectedText, tokens };
+}
+
+// Put protected tokens back, tolerating whitespace the model adds inside placeholders
+function restoreTokens(text, tokens) {
+    return text.replace(PLACEHOLDER_RESTORE_PATTERN, (match, i) => tokens[i] ?? match);
+}
+
+// Self-test sentences by source language ({0}/{1} mark the placeholders); English otherwise
+const PLACEHOLDER_TEST_SENTENCES = {
+    en: 'I like {0} phones and {1} cars.',
+    zh: '我喜欢{0}手机和{1}汽车。',
+    ja: '私は{0}の電話と{1}の車が好きです。',
+    ko: '나는 {0} 휴대폰과 {1} 자동차를 좋아한다.',
+    de: 'Ich mag {0} Handys und {1} Autos.',
+    fr: "J'aime les téléphones {0} et les voitures {1}.",
+    es: 'Me gustan los teléfonos {0} y los coches {1}.',
+    ru: 'Мне нравятся телефоны {0} и машины {1}.',
+};
+
+// Model keys already round-trip tested; a model is only tested the first time it is loaded
+const placeholderCheckedModels = new Set();
 
-    // Direct translation available
-    if (!needsPivotTranslation(fromLang, toLang)) {
-        const model = await getModel(fromKey, toKey);
-        return doTranslate(model, text);
+// Round-trip two placeholders through a freshly loaded model and warn when the
+// PLACEHOLDER_FORMAT does not survive it (protected terms would then leak or vanish)
+function checkPlaceholderRoundTrip(model) {
+    if (placeholderCheckedModels.has(model.key)) return;
+    placeholderCheckedModels.add(model.key);
+    const sentence = PLACEHOLDER_TEST_SENTENCES[normalizeLanguageCode(model.from)] || PLACEHOLDER_TEST_SENTENCES.en;
+    try {
+        const output = doTranslate(model, sentence.replace('{0}', placeholder(0)).replace('{1}', placeholder(1)));
+        const restored = restoreTokens(output, ['\u0000A', '\u0000B']);
+        if (!restored.includes('\u0000A') || !restored.includes('\u0000B')) {
+            console.warn(`[Server] Placeholder format ${CONFIG.PLACEHOLDER_FORMAT} does not survive model ${model.key}: ${JSON.stringify(output)}`);
+        }
+    } catch (err) {
+        console.warn(`[Server] Placeholder self-test failed for ${model.key}: ${err.message}`);
+    }
+}
+
+// Whole elements (tag, attributes and content) for skip_tags, e.g. ["code", "pre", "kbd"],
+// so their content is never sent to the model. Elements of one tag must not nest.
+function skipTagsPattern(tags) {
+    // Tag names are case-insensitive, but the combined pattern is not (glossary terms are exact)
+    const names = tags.map(tag => tag.replace(/[a-z]/gi, c => `[${c.toLowerCase()}${c.toUpperCase()}]`));
+    return `<(?<skipTag>${names.join('|')})\\b[^>]*>[\\s\\S]*?<\\/\\k<skipTag>\\s*>`;
+}
+
+function isValidSkipTags(tags) {
+    return Array.isArray(tags) && tags.every(tag => typeof tag === 'string' && /^[a-z][a-z0-9-]*$/i.test(tag));
+}
+
+// Glossary: { "source term": "target term" } pairs forced through placeholders
+function isValidGlossary(glossary) {
+    return glossary && typeof glossary === 'object' && !Array.isArray(glossary)
+        && Object.entries(glossary).every(([src, tgt]) => src && typeof tgt === 'string');
+}
+
+// Alternation of glossary source terms, longest first so overlapping terms match greedily
+function glossaryPattern(glossary) {
+    return Object.keys(glossary)
+        .sort((a, b) => b.length - a.length)
+        .map(escapeRegExp)
+        .join('|');
+}
+
+// Deployment glossary from GLOSSARY_FILE, editable at runtime through /glossary.
+// A request's own glossary takes precedence over it term by term.
+let serverGlossary = {};
+
+function loadGlossaryFile() {
+    if (!CONFIG.GLOSSARY_FILE) return {};
+    try {
+        const glossary = JSON.parse(fsSync.readFileSync(CONFIG.GLOSSARY_FILE, 'utf8'));
+        if (!isValidGlossary(glossary)) throw new Error('expected an object mapping source terms to target strings');
+        console.log(`[Server] Loaded ${Object.keys(glossary).length} glossary terms from ${CONFIG.GLOSSARY_FILE}`);
+        return glossary;
+    } catch (err) {
+        console.warn(`[Server] Cannot load GLOSSARY_FILE (${err.message}), starting with an empty glossary`);
+        return {};
+    }
+}
+
+async function saveGlossaryFile() {
+    await fs.writeFile(CONFIG.GLOSSARY_FILE, JSON.stringify(serverGlossary, null, 2) + '\n');
+}
+
+// Glossary targets whose source term occurs in `source` but that are absent from `translated`
+function missingGlossaryTerms(glossary, source, translated) {
+    return Object.entries(glossary)
+        .filter(([src, tgt]) => source.includes(src) && !translated.includes(tgt))
+        .map(([, tgt]) => tgt);
+}
+
+// Whitespace policy for request text: 'preserve' reattaches the exact leading/trailing
+// whitespace, 'collapse' squeezes every run to one space, 'trim' only strips the ends
+const WHITESPACE_MODES = ['preserve', 'collapse', 'trim'];
+
+// Returns { lead, body, trail }: body is what gets translated, lead/trail are put back around it
+function applyWhitespacePolicy(text, mode = 'preserve') {
+    if (mode === 'collapse') return { lead: '', body: text.replace(/\s+/g, ' ').trim(), trail: '' };
+    if (mode === 'trim') return { lead: '', body: text.trim(), trail: '' };
+    const match = text.match(/^(\s*)([\s\S]*?)(\s*)$/);
+    return { lead: match[1], body: match[2], trail: match[3] };
+}
+
+// Translate short text with a disambiguating context ("dialog button" for "Close"): the model sees
+// "context ⟦n⟧ text", with a placeholder numbered past any already in the input as the boundary,
+// and only what follows that placeholder in its output is kept. Only if the model drops the
+// placeholder is the text translated again on its own, so the context never leaks.
+function translateWithContext(model, text, context) {
+    const prefix = context.replace(/\s+/g, ' ').trim();
+    if (!prefix) return doTranslate(model, text);
+    const used = Array.from(`${prefix} ${text}`.matchAll(PLACEHOLDER_RESTORE_PATTERN), m => Number(m[1]));
+    const marker = Math.max(-1, ...used) + 1;
+    const output = doTranslate(model, `${prefix} ${placeholder(marker)} ${text}`);
+    const boundary = Array.from(output.matchAll(PLACEHOLDER_RESTORE_PATTERN)).find(m => Number(m[1]) === marker);
+    if (!boundary) return doTranslate(model, text);
+    return output.slice(boundary.index + boundary[0].length).trim();
+}
+
+// Translate text with the per-request options (whitespace policy, glossary, placeholders, entities, foreign script, context, ...) applied
+function translateText(model, text, options = {}) {
+    const prepared = prepareForTranslation(text, options, model.from);
+    if (prepared.body === null) return prepared.lead + prepared.trail;
+    const translated = options.context ? translateWithContext(model, prepared.body, options.context) : doTranslate(model, prepared.body);
+    return finishTranslation(prepared, translated, options);
+}
+
+// translateText along a pivot route [from, ...pivots, to]: spans are protected once and carried
+// through every leg as placeholders. The context only prefixes the first leg, which reads the
+// source language. `modelsUsed`, when given, collects each leg's model directory name.
+async function translateTextVia(route, text, options = {}, domain = null, quality = 'fast', modelsUsed = null) {
+    const prepared = prepareForTranslation(text, options, route[0]);
+    if (prepared.body === null) return prepared.lead + prepared.trail;
+    let translated = prepared.body;
+    for (let i = 1; i < route.length; i++) {
+        const model = await getModel(route[i - 1], route[i], domain, quality);
+        modelsUsed?.push(modelDirName(model));
+        translated = i === 1 && options.context ? translateWithContext(model, translated, options.context) : doTranslate(model, translated);
+    }
+    return finishTranslation(prepared, translated, options);
+}
+
+// The model input for translateText: whitespace split off per the policy, and skipped elements,
+// glossary terms, placeholders and entities swapped for numbered placeholders (`tokens`, or null
+// when nothing is protected). `body` is null when there is nothing to translate.
+function prepareForTranslation(text, options, sourceLang) {
+    const { lead, body, trail } = applyWhitespacePolicy(text, options.whitespace);
+    if (!body) return { lead, body: null, trail, tokens: null };
+
+    // Skipped elements, glossary terms and entities share one placeholder numbering, so they go
+    // through a single pattern; skipped elements come first so nothing inside them is matched
+    const patterns = [];
+    if (options.skipTags?.length > 0) patterns.push(skipTagsPattern(options.skipTags));
+    if (options.glossary && Object.keys(options.glossary).length > 0) patterns.push(glossaryPattern(options.glossary));
+    if (options.protectPlaceholders) patterns.push(FORMAT_PLACEHOLDER_PATTERN.source);
+    if (options.preserveEntities) patterns.push(ENTITY_PATTERN.source);
+    if (options.protectForeignScript) patterns.push(foreignScriptPattern(sourceLang));
+    if (patterns.length === 0) return { lead, body, trail, tokens: null };
+    const { text: protectedText, tokens } = protectTokens(body, new RegExp(patterns.join('|'), 'gu'));
+    return { lead, body: protectedText, trail, tokens };
+}
+
+// Put the protected spans (glossary terms as their targets) back into the model output
+function finishTranslation({ lead, trail, tokens }, translated, options) {
+    if (!tokens) return lead + translated + trail;
+    const replacements = tokens.map(token => Object.hasOwn(options.glossary || {}, token) ? options.glossary[token] : token);
+    if (options.glossaryApplied) countGlossaryApplied(translated, tokens, options.glossary, options.glossaryApplied);
+    return lead + restoreTokens(translated, replacements) + trail;
+}
+
+// report_glossary: count, per source term, the glossary placeholders that came back from the
+// model (and so were replaced by their target); terms whose placeholder was lost are not counted
+function countGlossaryApplied(translated, tokens, glossary, counts) {
+    for (const [, i] of translated.matchAll(PLACEHOLDER_RESTORE_PATTERN)) {
+        const token = tokens[i];
+        if (token !== undefined && Object.hasOwn(glossary || {}, token)) counts.set(token, (counts.get(token) || 0) + 1);
+    }
+}
+
+// ============== CSV ==============
+
+// RFC 4180 parser that keeps each cell's raw text and each row's line ending, so cells
+// that are not translated are written back byte-for-byte.
+// Returns [{ cells: [{ value, raw }], eol }].
+function parseCsv(text, delimiter = ',') {
+    const rows = [];
+    let cells = [];
+    let i = 0;
+    while (true) {
+        const start = i;
+        let value = '';
+        if (text[i] === '"') {
+            i++;
+            while (true) {
+                if (i >= text.length) throw new Error(`Unterminated quoted field at offset ${start}`);
+                if (text[i] === '"') {
+                    if (text[i + 1] !== '"') { i++; break; }
+                    value += '"';
+                    i += 2;
+                } else {
+                    value += text[i++];
+                }
+            }
+        }
+        // Unquoted field, or anything trailing a closing quote, runs to the next delimiter/newline
+        while (i < text.length && text[i] !== delimiter && text[i] !== '\n' && text[i] !== '\r') {
+            value += text[i++];
+        }
+        cells.push({ value, raw: text.slice(start, i) });
+
+        if (text[i] === delimiter) {
+            i++;
+            continue;
+        }
+        const eol = text.startsWith('\r\n', i) ? '\r\n' : (i < text.length ? text[i] : '');
+        i += eol.length;
+        rows.push({ cells, eol });
+        cells = [];
+        if (i >= text.length) return rows;
+    }
+}
+
+function serializeCsv(rows, delimiter = ',') {
+    return rows.map(row => row.cells.map(cell => cell.raw).join(delimiter) + row.eol).join('');
+}
+
+// Quote a cell when it needs it (delimiter, quote, newline, edge spaces) or was quoted before
+function quoteCsvCell(value, delimiter, wasQuoted) {
+    const needsQuotes = wasQuoted || value.includes(delimiter) || /["\r\n]|^\s|\s$/.test(value);
+    return needsQuotes ? `"${value.replace(/"/g, '""')}"` : value;
+}
+
+// ============== Key-Value Files ==============
+
+// .properties / .env parser. Comments, blank lines and keys are kept as raw text; a value
+// continued with a trailing backslash is read as one logical value.
+// Returns [{ raw }] for untouched lines and [{ raw, prefix, value, quote, ascii, eol }] for entries.
+function parseKeyValues(text) {
+    const lines = text.split(/(?<=\n)/);
+    const entries = [];
+    for (let i = 0; i < lines.length; i++) {
+        let raw = lines[i];
+        const eolOf = (line) => line.match(/\r?\n$/)?.[0] || '';
+        let eol = eolOf(raw);
+        let logical = raw.slice(0, raw.length - eol.length);
+        if (!logical.trim() || /^\s*[#!]/.test(logical)) {
+            entries.push({ raw });
+            continue;
+        }
+        // An odd number of trailing backslashes continues the value on the next line
+        while (/(?:^|[^\\])(?:\\\\)*\\$/.test(logical) && i + 1 < lines.length) {
+            const next = lines[++i];
+            raw += next;
+            eol = eolOf(next);
+            logical = logical.slice(0, -1) + next.slice(0, next.length - eol.length).replace(/^\s+/, '');
+        }
+        const match = logical.match(/^(\s*(?:export\s+)?(?:\\.|[^\s=:\\])+(?:\s*[=:]\s*|\s+))([\s\S]*)$/);
+        if (!match || !match[2].trim()) {
+            entries.push({ raw });
+            continue;
+        }
+        const [, prefix, rawValue] = match;
+        const quoted = rawValue.match(/^(["'])([\s\S]*)\1$/);
+        const entry = { raw, prefix, quote: quoted?.[1] || '', ascii: /\\u[0-9a-fA-F]{4}/.test(rawValue), eol };
+        if (entry.quote === "'") {
+            entry.value = quoted[2];
+        } else {
+            entry.value = (quoted ? quoted[2] : rawValue).replace(/\\(u[0-9a-fA-F]{4}|[\s\S])/g, (_, c) => {
+                if (c.length === 5) return String.fromCharCode(parseInt(c.slice(1), 16));
+                return { n: '\n', r: '\r', t: '\t', f: '\f' }[c] ?? c;
+            });
+        }
+        entries.push(entry);
+    }
+    return entries;
+}
+
+// Write a value back in its original quoting; non-ASCII is \u-escaped if the source used \u escapes
+function formatKeyValue(value, quote, ascii) {
+    if (quote === "'" && !/['\r\n]/.test(value)) return `'${value}'`;
+    let escaped = value.replace(/\\/g, '\\\\').replace(/\n/g, '\\n').replace(/\r/g, '\\r').replace(/\t/g, '\\t');
+    if (ascii) escaped = escaped.replace(/[^\x00-\x7f]/g, c => `\\u${c.charCodeAt(0).toString(16).padStart(4, '0')}`);
+    if (quote) return `"${escaped.replace(/"/g, '\\"')}"`;
+    return escaped.replace(/^ /, '\\ ');
+}
+
+// ============== Protobuf ==============
+
+// Minimal proto3 codec for the /translate messages in public/translate.proto: strings, bools
+// and repeated strings, with unknown fields skipped and known fields sent with the wrong wire
+// type rejected. Field number -> [name, type].
+const PROTOBUF_TYPE = 'application/x-protobuf';
+const TRANSLATE_REQUEST_PROTO = {
+    1: ['text', 'string'],
+    2: ['from', 'string'],
+    3: ['to', 'string'],
+    4: ['domain', 'string'],
+    5: ['quality', 'string'],
+    6: ['preserve_entities', 'bool'],
+    7: ['whitespace', 'string'],
+    8: ['context', 'string'],
+    9: ['protect_placeholders', 'bool'],
+    10: ['reverse', 'bool'],
+    11: ['skip_tags', 'repeated string'],
+    12: ['romanize', 'bool'],
+    13: ['bidi_marks', 'bool'],
+    14: ['protect_foreign_script', 'bool'],
+};
+const TRANSLATE_RESPONSE_PROTO = {
+    1: ['text', 'string'],
+    2: ['from', 'string'],
+    3: ['to', 'string'],
+    4: ['skipped', 'bool'],
+    5: ['output_guard', 'string'],
+    6: ['romanized', 'string'],
+};
+
+function decodeProtobuf(buffer, fields) {
+    let pos = 0;
+    const varint = () => {
+        let value = 0;
+        for (let shift = 1; ; shift *= 128) {
+            if (pos >= buffer.length) throw new Error('truncated varint');
+            const byte = buffer[pos++];
+            value += (byte & 0x7f) * shift;
+            if (byte < 0x80) return value;
+        }
+    };
+    const message = {};
+    while (pos < buffer.length) {
+        const tag = varint();
+        const [name, type] = fields[Math.floor(tag / 8)] || [];
+        if (name && tag % 8 !== (type === 'bool' ? 0 : 2)) {
+            throw new Error(`field ${Math.floor(tag / 8)} (${name}) has wire type ${tag % 8}, expected ${type === 'bool' ? 0 : 2}`);
+        }
+        let value;
+        switch (tag % 8) {
+            case 0: value = varint(); break;
+            case 1: pos += 8; break;
+            case 2: {
+                const length = varint();
+                if (pos + length > buffer.length) throw new Error('truncated field');
+                value = buffer.toString('utf8', pos, pos + length);
+                pos += length;
+                break;
+            }
+            case 5: pos += 4; break;
+            default: throw new Error(`unsupported wire type ${tag % 8}`);
+        }
+        if (!name || value === undefined) continue;
+        if (type === 'bool') message[name] = value !== 0;
+        else if (type === 'repeated string') (message[name] ||= []).push(value);
+        else message[name] = value;
+    }
+    return message;
+}
+
+// Fields at their proto3 default (empty, false, missing) are omitted
+function encodeProtobuf(message, fields) {
+    const bytes = [];
+    const varint = (value) => {
+        while (value > 0x7f) {
+            bytes.push((value % 128) | 0x80);
+            value = Math.floor(value / 128);
+        }
+        bytes.push(value);
+    };
+    const string = (number, value) => {
+        const data = Buffer.from(value, 'utf8');
+        varint(number * 8 + 2);
+        varint(data.length);
+        bytes.push(...data);
+    };
+    for (const [number, [name, type]] of Object.entries(fields)) {
+        const value = message[name];
+        if (value === undefined || value === null || value === '' || value === false) continue;
+        if (type === 'bool') {
+            varint(number * 8);
+            varint(1);
+        } else if (type === 'repeated string') {
+            for (const item of value) string(number, item);
+        } else {
+            string(number, String(value));
+        }
     }
+    return Buffer.from(bytes);
+}
+
+// Body parser for /translate: protobuf requests are decoded into the same fields as JSON
+const protobufBody = [
+    express.raw({ type: PROTOBUF_TYPE }),
+    (req, res, next) => {
+        if (!Buffer.isBuffer(req.body)) return next();
+        try {
+            req.body = decodeProtobuf(req.body, TRANSLATE_REQUEST_PROTO);
+        } catch (err) {
+            return badRequest(res, `Invalid protobuf body: ${err.message}`);
+        }
+        req.protobuf = true;
+        next();
+    },
+];
+
+// ============== Romanization ==============
+
+// Readings for `romanize: true`: pinyin for Chinese (pinyin-pro), Hepburn for Japanese kana,
+// Revised Romanization for Hangul, and a BGN/PCGN-style table for Cyrillic. Kanji have no
+// reading without a dictionary and are left as they are.
+const KANA_ROMAJI = Object.fromEntries(`あa いi うu えe おo かka きki くku けke こko がga ぎgi ぐgu げge ごgo
+    さsa しshi すsu せse そso ざza じji ずzu ぜze ぞzo たta ちchi つtsu てte とto だda ぢji づzu でde どdo
+    なna にni ぬnu ねne のno はha ひhi ふfu へhe ほho ばba びbi ぶbu べbe ぼbo ぱpa ぴpi ぷpu ぺpe ぽpo
+    まma みmi むmu めme もmo やya ゆyu よyo らra りri るru れre ろro わwa をo んn ゔvu ぁa ぃi ぅu ぇe ぉo`
+    .split(/\s+/).map(pair => [pair[0], pair.slice(1)]));
+
+function romanizeKana(text) {
+    let out = '';
+    let geminate = false;
+    for (const char of text) {
+        const code = char.codePointAt(0);
+        // Katakana maps onto hiragana 0x60 code points lower
+        const kana = code >= 0x30a1 && code <= 0x30f6 ? String.fromCodePoint(code - 0x60) : char;
+        if (kana === 'っ') {
+            geminate = true;
+            continue;
+        }
+        if ('ゃゅょ'.includes(kana) && /i$/.test(out)) {
+            // きゃ -> kya, しゃ -> sha
+            const vowel = { 'ゃ': 'a', 'ゅ': 'u', 'ょ': 'o' }[kana];
+            out = /(sh|ch|j)i$/.test(out) ? out.slice(0, -1) + vowel : out.slice(0, -1) + 'y' + vowel;
+            continue;
+        }
+        if (kana === 'ー') {
+            out += out.match(/[aeiou]$/)?.[0] || '';
+            continue;
+        }
+        let romaji = KANA_ROMAJI[kana] ?? char;
+        if (geminate && /^[a-z]/.test(romaji)) romaji = (romaji.startsWith('ch') ? 't' : romaji[0]) + romaji;
+        geminate = false;
+        out += romaji;
+    }
+    return out;
+}
+
+const HANGUL_INITIALS = ['g', 'kk', 'n', 'd', 'tt', 'r', 'm', 'b', 'pp', 's', 'ss', '', 'j', 'jj', 'ch', 'k', 't', 'p', 'h'];
+const HANGUL_VOWELS = ['a', 'ae', 'ya', 'yae', 'eo', 'e', 'yeo', 'ye', 'o', 'wa', 'wae', 'oe', 'yo', 'u', 'wo', 'we', 'wi', 'yu', 'eu', 'ui', 'i'];
+const HANGUL_FINALS = ['', 'k', 'k', 'k', 'n', 'n', 'n', 't', 'l', 'k', 'm', 'l', 'l', 'l', 'p', 'l', 'm', 'p', 'p', 't', 't', 'ng', 't', 't', 'k', 't', 'p', 't'];
+// Single final consonants carried over to a following vowel (한국어 -> hangugeo)
+const HANGUL_LINKED_FINALS = { 1: 'g', 2: 'kk', 4: 'n', 7: 'd', 8: 'r', 16: 'm', 17: 'b', 19: 's', 20: 'ss', 21: 'ng', 22: 'j', 23: 'ch', 24: 'k', 25: 't', 26: 'p', 27: '' };
+
+function romanizeHangul(text) {
+    const chars = [...text];
+    return chars.map((char, i) => {
+        const index = char.codePointAt(0) - 0xac00;
+        if (index < 0 || index >= 11172) return char;
+        const final = index % 28;
+        const next = (chars[i + 1]?.codePointAt(0) ?? 0) - 0xac00;
+        const nextStartsWithVowel = next >= 0 && next < 11172 && Math.floor(next / 588) === 11;
+        const coda = nextStartsWithVowel && final in HANGUL_LINKED_FINALS ? HANGUL_LINKED_FINALS[final] : HANGUL_FINALS[final];
+        return HANGUL_INITIALS[Math.floor(index / 588)] + HANGUL_VOWELS[Math.floor((index % 588) / 28)] + coda;
+    }).join('');
+}
+
+const CYRILLIC_LATIN = {
+    а: 'a', б: 'b', в: 'v', г: 'g', д: 'd', е: 'e', ё: 'yo', ж: 'zh', з: 'z', и: 'i', й: 'y', к: 'k',
+    л: 'l', м: 'm', н: 'n', о: 'o', п: 'p', р: 'r', с: 's', т: 't', у: 'u', ф: 'f', х: 'kh', ц: 'ts',
+    ч: 'ch', ш: 'sh', щ: 'shch', ъ: '', ы: 'y', ь: '', э: 'e', ю: 'yu', я: 'ya', і: 'i', ї: 'yi', є: 'ye', ґ: 'g',
+};
 
-    // Pivot via English
-    const intermediateModel = await getModel(fromKey, 'en');
-    const intermediate = doTranslate(intermediateModel, text);
+function romanizeCyrillic(text) {
+    return text.replace(/[Ѐ-ӿ]/g, char => {
+        const lower = char.toLowerCase();
+        const latin = CYRILLIC_LATIN[lower] ?? char;
+        return char === lower ? latin : latin.charAt(0).toUpperCase() + latin.slice(1);
+    });
+}
 
-    const finalModel = await getModel('en', toKey);
-    return doTranslate(finalModel, intermediate);
+// Romanized reading of `text` in language `lang`, or null when there is nothing to romanize
+function romanizeText(text, lang) {
+    const base = normalizeLanguageCode(lang).split(/[-_]/)[0];
+    if (base === 'zh') return pinyin(text, { nonZh: 'consecutive' });
+    if (base === 'ja') return romanizeKana(text);
+    if (base === 'ko') return romanizeHangul(text);
+    if (/[Ѐ-ӿ]/.test(text)) return romanizeCyrillic(text);
+    return null;
 }
 
 // ============== Model Loading ==============
@@ -370,7 +1340,7 @@ async function loadModelFiles(modelPath) {
             }
         }
     } catch (err) {
-        throw new Error(`Cannot read model directory: ${err.message}`);
+        throw new ApiError(500, 'MODEL_LOAD_FAILED', `Cannot read model directory: ${err.message}`);
     }
 
     // Validate required files exist
@@ -396,7 +1366,7 @@ async function loadModelFiles(modelPath) {
     const missingFiles = required.filter(k => !files[k] || files[k].length === 0);
 
     if (missingFiles.length > 0) {
-        throw new Error(`Missing required files: ${missingFiles.map(f => missing[f]).join(', ')}`);
+        throw new ApiError(500, 'MODEL_LOAD_FAILED', `Missing required files: ${missingFiles.map(f => missing[f]).join(', ')}`);
     }
 
     // Read the first matching file for each type
@@ -407,9 +1377,40 @@ async function loadModelFiles(modelPath) {
         }
     }
 
+    // Optional checksums.txt (sha256sum format) guards against corrupted or partial files
+    const checksums = await readChecksums(modelPath);
+    if (checksums) {
+        for (const [key, arr] of Object.entries(files)) {
+            const expected = arr?.length > 0 && checksums.get(arr[0].name);
+            if (!expected) continue;
+            const actual = crypto.createHash('sha256').update(result[key]).digest('hex');
+            if (actual !== expected) {
+                throw new ApiError(500, 'MODEL_CHECKSUM_MISMATCH', `Checksum mismatch for ${arr[0].name} in ${modelPath}: expected ${expected}, got ${actual}`);
+            }
+        }
+    }
+
     return result;
 }
 
+// Parse `checksums.txt` ("<sha256>  <file>" per line, as written by sha256sum) into
+// Map<file name, hash>, or null when the model directory has none
+async function readChecksums(modelPath) {
+    let text;
+    try {
+        text = await fs.readFile(path.join(modelPath, 'checksums.txt'), 'utf8');
+    } catch (err) {
+        if (err.code === 'ENOENT') return null;
+        throw new ApiError(500, 'MODEL_LOAD_FAILED', `Cannot read checksums.txt: ${err.message}`);
+    }
+    const checksums = new Map();
+    for (const line of text.split(/\r?\n/)) {
+        const match = line.trim().match(/^([0-9a-f]{64})\s+\*?(.+)$/i);
+        if (match) checksums.set(path.basename(match[2].trim()), match[1].toLowerCase());
+    }
+    return checksums;
+}
+
 function createAlignedMemory(buffer, alignment = 64) {
     // Use the active model's bergamot module, or create a temporary one
     const bergamot = activeModel ? activeModel.bergamot : null;
@@ -422,11 +1423,23 @@ function createAlignedMemory(buffer, alignment = 64) {
     return aligned;
 }
 
+// Error for a pair with no registered model; lists what is available so clients can prompt the user
+function unsupportedPairError(key) {
+    // Domain and quality keys ("en-zh:medical") name the same languages as the plain pair
+    const [from, to] = key.split(':')[0].split('-');
+    const models = Array.from(availableModels.values());
+    return new ApiError(400, 'MODEL_NOT_AVAILABLE', `Translation from '${from}' to '${to}' is not supported`, {
+        available_sources: [...new Set(models.map(m => m.from))].sort(),
+        available_targets: [...new Set(models.map(m => m.to))].sort(),
+    });
+}
+
 // Load model into WASM (unloads previous model if any)
 async function loadModel(key) {
+    key = canonicalModelKey(key);
     const modelInfo = availableModels.get(key);
     if (!modelInfo) {
-        throw new Error(`Model not available: ${key}`);
+        throw unsupportedPairError(key);
     }
 
     // If already active, return it
@@ -452,33 +1465,103 @@ async function loadModel(key) {
     }
 }
 
-async function doLoadModel(key, modelInfo) {
-    // Unload previous model to free WASM memory
-    if (activeModel) {
-        console.log(`[Server] Unloading previous model: ${activeModel.key}`);
-        try {
-            activeModel.instance.delete();
-            activeModel.service.delete();
-            // Delete the WASM module to free memory
-            if (activeModel.bergamot) {
-                // bergamot cleanup is handled by deleting model/service
-            }
-        } catch (e) {
-            // Ignore cleanup errors
-        }
-        activeModel = null;
+function unloadActiveModel() {
+    if (!activeModel) return;
+    console.log(`[Server] Unloading previous model: ${activeModel.key}`);
+    try {
+        activeModel.instance.delete();
+        activeModel.service.delete();
+        // bergamot cleanup is handled by deleting model/service
+    } catch (e) {
+        // Ignore cleanup errors
     }
+    activeModel = null;
+}
 
-    // Load model files if not cached
-    if (!modelInfo.buffers) {
-        console.log(`[Server] Loading model files: ${key}`);
-        modelInfo.buffers = await loadModelFiles(modelInfo.dir);
+// Marian options passed to every TranslationModel. BERGAMOT_CONFIG_FILE replaces this
+// deployment-wide; a PAIR_CONFIG_FILE inside a model directory overrides both for that pair.
+const DEFAULT_BERGAMOT_CONFIG = [
+    'beam-size: 1', 'normalize: 1.0', 'word-penalty: 0',
+    'max-length-break: 512', 'mini-batch-words: 1024', 'workspace: 128',
+    'max-length-factor: 2.0', 'skip-cost: true', 'cpu-threads: 0',
+    'quiet: true', 'quiet-translation: true',
+    'gemm-precision: int8shiftAlphaAll', 'alignment: soft'
+].join('\n');
+const PAIR_CONFIG_FILE = 'bergamot-config.yml';
+
+// Read BERGAMOT_CONFIG_FILE once at startup; a missing or unreadable file keeps the built-in default
+function loadBergamotConfigTemplate() {
+    if (!CONFIG.BERGAMOT_CONFIG_FILE) return DEFAULT_BERGAMOT_CONFIG;
+    try {
+        const template = fsSync.readFileSync(CONFIG.BERGAMOT_CONFIG_FILE, 'utf8').trim();
+        console.log(`[Server] Using Bergamot config template from ${CONFIG.BERGAMOT_CONFIG_FILE}`);
+        return template;
+    } catch (err) {
+        console.warn(`[Server] Cannot read BERGAMOT_CONFIG_FILE (${err.message}), using built-in config`);
+        return DEFAULT_BERGAMOT_CONFIG;
     }
+}
 
-    const { from, to, buffers } = modelInfo;
-    console.log(`[Server] Creating WASM instance for model: ${key}`);
+let bergamotConfigTemplate = DEFAULT_BERGAMOT_CONFIG;
 
-    // Create a new WASM instance per model (like MTranServer)
+async function bergamotConfigFor(dir) {
+    try {
+        return (await fs.readFile(path.join(dir, PAIR_CONFIG_FILE), 'utf8')).trim();
+    } catch (err) {
+        if (err.code !== 'ENOENT') {
+            console.warn(`[Server] Cannot read ${PAIR_CONFIG_FILE} in ${dir} (${err.message}), using deployment config`);
+        }
+        return bergamotConfigTemplate;
+    }
+}
+
+// Set "name: value" lines in a Bergamot config, replacing existing ones and appending the rest
+function withConfigOverrides(config, overrides) {
+    const pending = { ...overrides };
+    const lines = config.split('\n').map((line) => {
+        const name = line.split(':')[0].trim();
+        if (!Object.hasOwn(pending, name)) return line;
+        const value = pending[name];
+        delete pending[name];
+        return `${name}: ${value}`;
+    });
+    return [...lines, ...Object.entries(pending).map(([name, value]) => `${name}: ${value}`)].join('\n');
+}
+
+// Models with cached file buffers, least recently used first. With MODEL_CACHE_BUDGET set,
+// the oldest are dropped and re-read from disk the next time they are needed.
+const bufferUsage = new Map();
+
+function touchModelBuffers(key) {
+    bufferUsage.delete(key);
+    bufferUsage.set(key, true);
+    if (!CONFIG.MODEL_CACHE_BUDGET) return;
+    for (const lruKey of bufferUsage.keys()) {
+        if (bufferUsage.size <= CONFIG.MODEL_CACHE_BUDGET) break;
+        if (lruKey === key) continue;
+        const modelInfo = availableModels.get(lruKey);
+        if (modelInfo) modelInfo.buffers = null;
+        bufferUsage.delete(lruKey);
+        console.log(`[Server] Evicted cached buffers for ${lruKey} (MODEL_CACHE_BUDGET=${CONFIG.MODEL_CACHE_BUDGET})`);
+    }
+}
+
+async function doLoadModel(key, modelInfo) {
+    // Unload previous model to free WASM memory
+    unloadActiveModel();
+
+    // Load model files if not cached
+    if (!modelInfo.buffers) {
+        console.log(`[Server] Loading model files: ${key}`);
+        modelInfo.buffers = await loadModelFiles(modelInfo.dir);
+        delete modelInfo.loadError;
+    }
+    touchModelBuffers(key);
+
+    const { from, to, buffers } = modelInfo;
+    console.log(`[Server] Creating WASM instance for model: ${key}`);
+
+    // Create a new WASM instance per model (like MTranServer)
     const bergamot = await createBergamotInstance();
 
     const aligned = {};
@@ -491,19 +1574,18 @@ async function doLoadModel(key, modelInfo) {
     vocabList.push_back(aligned.srcvocab);
     vocabList.push_back(aligned.trgvocab);
 
-    const config = [
-        'beam-size: 1', 'normalize: 1.0', 'word-penalty: 0',
-        'max-length-break: 512', 'mini-batch-words: 1024', 'workspace: 128',
-        'max-length-factor: 2.0', 'skip-cost: true', 'cpu-threads: 0',
-        'quiet: true', 'quiet-translation: true',
-        'gemm-precision: int8shiftAlphaAll', 'alignment: soft'
-    ].join('\n');
+    // PAIR_OPTIONS apply to every domain/quality variant of the pair, over its config file
+    const pairOptions = CONFIG.PAIR_OPTIONS.get(modelKey(from, to));
+    const baseConfig = await bergamotConfigFor(modelInfo.dir);
+    const config = pairOptions ? withConfigOverrides(baseConfig, pairOptions) : baseConfig;
+    if (pairOptions) console.log(`[Server] PAIR_OPTIONS for ${key}: ${Object.entries(pairOptions).map(([name, value]) => `${name}=${value}`).join(', ')}`);
 
     const instance = new bergamot.TranslationModel(from, to, config, aligned.model, aligned.lex, vocabList, null);
     const service = new bergamot.BlockingService({ cacheSize: 0 });
 
-    activeModel = { key, instance, service, from, to, aligned, vocabList, bergamot };
+    activeModel = { key, dir: modelInfo.dir, instance, service, from, to, aligned, vocabList, bergamot };
     console.log(`[Server] Model activated: ${key}`);
+    checkPlaceholderRoundTrip(activeModel);
     return activeModel;
 }
 
@@ -519,38 +1601,226 @@ function doTranslate(model, text) {
     let cleanedText = text.replace(/[\x00-\x08\x0B\x0C\x0E-\x1F\x7F]/g, '');
     cleanedText = cleanedText.replace(/\uFFFD/g, '');
 
+    let result = runBergamot(model, cleanedText);
+    // Bergamot occasionally returns nothing for real input: retry once, then pass the source through
+    if (!result.trim() && cleanedText.trim()) {
+        result = runBergamot(model, cleanedText);
+        if (!result.trim()) {
+            console.warn(`[Server] Empty translation from ${model.key} for ${cleanedText.length} char input after retry, returning source text`);
+            return text;
+        }
+    }
+    // VERIFY_DETERMINISM: translate a second time and warn if the model disagrees with itself
+    if (CONFIG.VERIFY_DETERMINISM) {
+        const again = runBergamot(model, cleanedText);
+        if (again !== result) {
+            console.warn(`[Server] Nondeterministic output from ${model.key} for ${cleanedText.length} char input: ${JSON.stringify(result)} vs ${JSON.stringify(again)}`);
+        }
+    }
+    return result;
+}
+
+function runBergamot(model, cleanedText) {
     const msgs = new model.bergamot.VectorString();
     const opts = new model.bergamot.VectorResponseOptions();
     try {
         msgs.push_back(cleanedText);
         opts.push_back({ qualityScores: false, alignment: false, html: false });
         const responses = model.service.translate(model.instance, msgs, opts);
+        if (responses.size() !== msgs.size()) {
+            const count = responses.size();
+            responses.delete();
+            throw new Error(`Bergamot returned ${count} results for ${msgs.size()} inputs`);
+        }
         const result = responses.get(0).getTranslatedText();
         responses.delete();
         msgs.delete();
         opts.delete();
         return result;
     } catch (err) {
+        // A trap leaves the WASM instance unusable: drop it so the next request builds a fresh
+        // one, and answer 502 rather than blaming the input
+        if (isEngineCrash(err)) {
+            engineCrashes++;
+            console.error(`[Server] Bergamot crashed while translating with ${model.key}: ${err.message}`);
+            if (activeModel === model) unloadActiveModel();
+            throw new ApiError(502, 'TRANSLATOR_UNAVAILABLE', `The translation engine crashed (${err.message}) and will be reloaded on the next request`, {}, 1);
+        }
         msgs.delete();
         opts.delete();
         throw err;
     }
 }
 
+// WASM traps (out-of-bounds access, unreachable, stack overflow) and Emscripten aborts
+let engineCrashes = 0;
+function isEngineCrash(err) {
+    return err instanceof WebAssembly.RuntimeError || /^(abort|Aborted)\(/.test(err?.message ?? String(err));
+}
+
+// Runaway-output guard: Bergamot occasionally loops and repeats itself on certain inputs.
+// Returns { text, guard } where guard is null, 'flagged' or 'truncated'; throws on 'reject'.
+const OUTPUT_GUARD_MIN_CHARS = 10; // ratios on very short inputs are meaningless
+
+function guardOutput(source, translated) {
+    if (!CONFIG.MAX_OUTPUT_RATIO || source.length < OUTPUT_GUARD_MIN_CHARS) {
+        return { text: translated, guard: null };
+    }
+    const limit = Math.ceil(source.length * CONFIG.MAX_OUTPUT_RATIO);
+    if (translated.length <= limit) {
+        return { text: translated, guard: null };
+    }
+
+    console.warn(`[Server] Output guard triggered: ${translated.length} chars for ${source.length} char input`);
+    switch (CONFIG.OUTPUT_GUARD_ACTION) {
+        case 'reject':
+            throw new ApiError(500, 'OUTPUT_GUARD_REJECTED', `Translation output is ${(translated.length / source.length).toFixed(1)}x the input length (limit ${CONFIG.MAX_OUTPUT_RATIO}x)`);
+        case 'truncate':
+            return { text: translated.slice(0, limit), guard: 'truncated' };
+        default:
+            return { text: translated, guard: 'flagged' };
+    }
+}
+
+// Pipe a translation through POST_PROCESS_CMD (stdin -> stdout). Opt-in; any failure
+// (spawn error, non-zero exit, timeout) logs a warning and returns the text unchanged.
+function postProcess(text) {
+    if (!CONFIG.POST_PROCESS_CMD) return Promise.resolve(text);
+    return new Promise((resolve) => {
+        const child = spawn(CONFIG.POST_PROCESS_CMD, { shell: true, stdio: ['pipe', 'pipe', 'pipe'] });
+        let stdout = '';
+        let stderr = '';
+        let settled = false;
+        const fallback = (reason) => {
+            if (settled) return;
+            settled = true;
+            clearTimeout(timer);
+            console.warn(`[Server] Post-processor failed (${reason}), returning unprocessed translation`);
+            resolve(text);
+        };
+        const timer = setTimeout(() => {
+            child.kill('SIGKILL');
+            fallback(`timed out after ${CONFIG.POST_PROCESS_TIMEOUT_MS}ms`);
+        }, CONFIG.POST_PROCESS_TIMEOUT_MS);
+
+        child.stdout.setEncoding('utf8').on('data', (chunk) => { stdout += chunk; });
+        child.stderr.setEncoding('utf8').on('data', (chunk) => { stderr += chunk; });
+        child.on('error', (err) => fallback(err.message));
+        child.on('close', (code) => {
+            if (code !== 0) return fallback(`exit ${code}${stderr ? `: ${stderr.trim()}` : ''}`);
+            if (settled) return;
+            settled = true;
+            clearTimeout(timer);
+            resolve(stdout);
+        });
+        child.stdin.on('error', () => {}); // EPIPE if the command exits without reading
+        child.stdin.end(text);
+    });
+}
+
+// Bounded LRU of detection results (Map iteration order = least recently used first). Keyed by
+// the expected languages as well as the text, since the answer depends on them and they change
+// when models are registered or recover from a load error.
+const DETECT_CACHE_MAX_TEXT = 2048; // longer texts are not cached to keep memory bounded
+const detectionCache = new Map();
+let detectionCacheHits = 0;
+let detectionCacheMisses = 0;
+
 function detectLanguage(text) {
+    if (!text || CONFIG.DETECT_CACHE_SIZE <= 0 || text.length > DETECT_CACHE_MAX_TEXT) {
+        return constrainToExpected(runDetector(text));
+    }
+
+    const key = `${expectedLanguages().join(',')}\n${text}`;
+    if (detectionCache.has(key)) {
+        detectionCacheHits++;
+        const language = detectionCache.get(key);
+        detectionCache.delete(key);
+        detectionCache.set(key, language);
+        return language;
+    }
+
+    detectionCacheMisses++;
+    const language = constrainToExpected(runDetector(text));
+    detectionCache.set(key, language);
+    if (detectionCache.size > CONFIG.DETECT_CACHE_SIZE) {
+        detectionCache.delete(detectionCache.keys().next().value);
+    }
+    return language;
+}
+
+// Map franc's 3-letter codes to 2-letter ISO 639-1 (franc reports Mandarin as 'cmn', Standard
+// Arabic as 'arb', Persian as 'pes', ...). Truncating the 3-letter code is not a safe guess
+// ('swe' is Swedish, 'sw' Swahili), so codes missing here are never guessed.
+const FRANC_CODE_MAP = {
+    'eng': 'en', 'zho': 'zh', 'cmn': 'zh', 'jpn': 'jp', 'kor': 'ko',
+    'fra': 'fr', 'deu': 'de', 'spa': 'es', 'rus': 'ru',
+    'por': 'pt', 'ita': 'it', 'nld': 'nl', 'pol': 'pl',
+    'ara': 'ar', 'arb': 'ar', 'hin': 'hi', 'tha': 'th', 'vie': 'vi',
+    'ukr': 'uk', 'bel': 'be', 'bul': 'bg', 'srp': 'sr', 'mkd': 'mk', 'kaz': 'kk',
+    'ces': 'cs', 'slk': 'sk', 'slv': 'sl', 'hrv': 'hr', 'bos': 'bs', 'hun': 'hu', 'ron': 'ro',
+    'swe': 'sv', 'dan': 'da', 'nob': 'nb', 'nno': 'nn', 'isl': 'is', 'fin': 'fi', 'est': 'et',
+    'lit': 'lt', 'lvs': 'lv', 'lav': 'lv', 'ell': 'el', 'tur': 'tr', 'azj': 'az', 'uzn': 'uz',
+    'heb': 'he', 'pes': 'fa', 'fas': 'fa', 'urd': 'ur', 'ben': 'bn', 'mar': 'mr', 'guj': 'gu',
+    'pan': 'pa', 'tam': 'ta', 'tel': 'te', 'kan': 'kn', 'mal': 'ml',
+    'ind': 'id', 'zsm': 'ms', 'msa': 'ms', 'tgl': 'tl', 'cat': 'ca', 'glg': 'gl', 'eus': 'eu',
+    'afr': 'af', 'swh': 'sw', 'als': 'sq', 'sqi': 'sq', 'kat': 'ka', 'hye': 'hy', 'mlt': 'mt',
+};
+
+// Unmapped codes fall back to the default source language (DEFAULT_SOURCE_LANG, else English)
+// instead of failing the request; each one is logged once so the table can be extended
+const unmappedFrancCodes = new Set();
+
+function mapFrancCode(code) {
+    if (Object.hasOwn(FRANC_CODE_MAP, code)) return FRANC_CODE_MAP[code];
+    const fallback = CONFIG.DEFAULT_SOURCE_LANG || 'en';
+    if (!unmappedFrancCodes.has(code)) {
+        unmappedFrancCodes.add(code);
+        console.warn(`[Server] Detected language code '${code}' has no ISO 639-1 mapping, using ${fallback}`);
+    }
+    return fallback;
+}
+
+// Languages detection may answer with: EXPECTED_LANGUAGES, narrowed to (or, when unset, replaced
+// by) the source languages of usable registered models with DETECT_LOADED_SOURCES_ONLY. Empty = any.
+function expectedLanguages() {
+    const expected = CONFIG.EXPECTED_LANGUAGES.map(normalizeLanguageCode);
+    if (!CONFIG.DETECT_LOADED_SOURCES_ONLY || availableModels.size === 0) return expected;
+    const sources = [...new Set(Array.from(availableModels.values())
+        .filter(m => !m.loadError)
+        .map(m => normalizeLanguageCode(m.from)))];
+    const loaded = expected.filter(lang => sources.includes(lang));
+    return loaded.length > 0 ? loaded : sources;
+}
+
+// Restrict franc to the expected languages when configured
+function francOnlyOption() {
+    const expected = expectedLanguages();
+    if (expected.length === 0) return {};
+    const only = Object.keys(FRANC_CODE_MAP)
+        .filter(code => expected.includes(normalizeLanguageCode(FRANC_CODE_MAP[code])));
+    return { only };
+}
+
+// Map a detected language into the expected languages: keep it if allowed, prefer a
+// same-script neighbour for CJK (zh <-> ja), otherwise the first expected language
+function constrainToExpected(language) {
+    const expected = expectedLanguages();
+    if (expected.length === 0 || expected.includes(normalizeLanguageCode(language))) {
+        return language;
+    }
+    const neighbours = { zh: ['ja', 'zh-Hant'], ja: ['zh', 'zh-Hant'], 'zh-Hant': ['zh', 'ja'] };
+    const nearest = (neighbours[normalizeLanguageCode(language)] || []).find(l => expected.includes(l));
+    return nearest || expected[0];
+}
+
+function runDetector(text) {
     if (!text || text.trim().length < 3) return 'en';
 
     // Use franc for detection
-    const result = franc(text, { minLength: 3, whitelisted: ['eng', 'zho', 'jpn', 'kor', 'fra', 'deu', 'spa', 'rus', 'por'] });
+    const result = franc(text, { minLength: 3, ...francOnlyOption(), whitelisted: ['eng', 'zho', 'jpn', 'kor', 'fra', 'deu', 'spa', 'rus', 'por'] });
     if (result !== 'und') {
-        // Map 3-letter codes to 2-letter ISO 639-1
-        const codeMap = {
-            'eng': 'en', 'zho': 'zh', 'jpn': 'jp', 'kor': 'ko',
-            'fra': 'fr', 'deu': 'de', 'spa': 'es', 'rus': 'ru',
-            'por': 'pt', 'ita': 'it', 'nld': 'nl', 'pol': 'pl',
-            'ara': 'ar', 'hin': 'hi', 'tha': 'th', 'vie': 'vi',
-        };
-        return codeMap[result] || result.slice(0, 2);
+        return mapFrancCode(result);
     }
 
     // Simple heuristic fallback for CJK
@@ -564,6 +1834,77 @@ function detectLanguage(text) {
     return 'en';
 }
 
+// Detect with a confidence in [0, 1]: the margin between franc's best and runner-up scores
+// (franc normalizes the best candidate to 1). Undetermined results have confidence 0.
+function detectLanguageWithConfidence(text) {
+    const [top, second] = francAll(text || '', { minLength: 3, ...francOnlyOption() });
+    if (!top || top[0] === 'und') {
+        return { language: detectLanguage(text), confidence: 0 };
+    }
+    return { language: constrainToExpected(mapFrancCode(top[0])), confidence: second ? 1 - second[1] : 1 };
+}
+
+// Below this confidence an auto-detected source is considered uncertain
+const UNCERTAIN_DETECTION_CONFIDENCE = 0.5;
+
+// Top detection candidates as [{ lang, conf }], with conf normalized so the list sums to 1
+function detectionAlternatives(text, limit = 3) {
+    const seen = new Set();
+    const candidates = [];
+    for (const [code, score] of francAll(text || '', { minLength: 3, ...francOnlyOption() })) {
+        if (code === 'und') continue;
+        const lang = constrainToExpected(mapFrancCode(code));
+        if (seen.has(lang)) continue;
+        seen.add(lang);
+        candidates.push({ lang, score });
+        if (candidates.length === limit) break;
+    }
+    const total = candidates.reduce((sum, c) => sum + c.score, 0) || 1;
+    return candidates.map(({ lang, score }) => ({ lang, conf: Math.round((score / total) * 100) / 100 }));
+}
+
+// Kana or Hangul settle the language without a statistical model; confidence is the
+// share of letters in that script (kanji count towards Japanese once kana are present)
+const SCRIPT_RULES = [
+    { test: /[\u3040-\u309f\u30a0-\u30ff]/, letters: /[\u3040-\u309f\u30a0-\u30ff\u4e00-\u9fff]/g, code: 'jpn' },
+    { test: /[\uac00-\ud7af]/, letters: /[\uac00-\ud7af]/g, code: 'kor' },
+];
+
+// Detect one text for POST /detect batches: { language, confidence, method: 'script' | 'statistical' }
+function detectWithMethod(text) {
+    const letterCount = (text.match(/\p{L}/gu) || []).length;
+    for (const rule of SCRIPT_RULES) {
+        if (letterCount > 0 && rule.test.test(text)) {
+            const confidence = Math.min(1, (text.match(rule.letters) || []).length / letterCount);
+            return { language: constrainToExpected(mapFrancCode(rule.code)), confidence, method: 'script' };
+        }
+    }
+    return { ...detectLanguageWithConfidence(text), method: 'statistical' };
+}
+
+// Whether text is confidently already in the target language (SKIP_WHEN_DETECTED_TARGET),
+// regardless of the `from` the client claimed
+function isAlreadyInTarget(text, to) {
+    if (!CONFIG.SKIP_WHEN_DETECTED_TARGET) return false;
+    const { language, confidence } = detectLanguageWithConfidence(text);
+    return confidence >= CONFIG.SKIP_DETECTED_TARGET_CONFIDENCE
+        && normalizeLanguageCode(language) === normalizeLanguageCode(to);
+}
+
+// Resolve the source language for a request: explicit `from`, or auto-detection.
+// Detection on very short text is near-random, so below DETECT_MIN_CHARS use
+// DEFAULT_SOURCE_LANG, or the only source language among registered models.
+function resolveSourceLang(from, text) {
+    if (from && from !== 'auto') return from;
+
+    if ((text || '').trim().length < CONFIG.DETECT_MIN_CHARS) {
+        if (CONFIG.DEFAULT_SOURCE_LANG) return CONFIG.DEFAULT_SOURCE_LANG;
+        const sources = new Set(Array.from(availableModels.values(), m => m.from));
+        if (sources.size === 1) return [...sources][0];
+    }
+    return detectLanguage(text);
+}
+
 // Simple language code to name mapping for HCFY
 function getLangName(code) {
     const map = {
@@ -585,39 +1926,136 @@ function convertLangName(name) {
     return langCodeMap[name] || name;
 }
 
-// Get or load model for translation
-async function getModel(from, to) {
-    const key = `${from}-${to}`;
+// Model key for a pair, with an optional domain variant (e.g. "en-zh:medical")
+// Registry key for a pair; a manifest alias resolves to the model it names, so both keys share
+// one loaded model, one rate limit window and one set of PAIR_OPTIONS
+function modelKey(from, to, domain = null) {
+    return canonicalModelKey(domain ? `${from}-${to}:${domain}` : `${from}-${to}`);
+}
+
+function canonicalModelKey(key) {
+    return modelAliases.get(key) ?? key;
+}
+
+// Aliases registered for a model, for /models and /health
+function aliasesOf(key) {
+    return Array.from(modelAliases.keys()).filter(alias => modelAliases.get(alias) === key);
+}
+
+// Quality tiers map to model directory suffixes: `enzh-fast` and `enzh-hq` sit next to (or
+// replace) the generic `enzh`. Requests default to the fast tier.
+const QUALITY_VARIANTS = { fast: 'fast', high: 'hq' };
+
+// Get or load model for translation. Preference order: the requested domain, the requested
+// quality tier, the generic model, then any other quality tier of the pair. PAIR_RATE_LIMITS
+// are charged by the route handlers, since one request may need the model more than once.
+async function getModel(from, to, domain = null, quality = 'fast') {
+    const candidates = [
+        domain && modelKey(from, to, domain),
+        modelKey(from, to, QUALITY_VARIANTS[quality]),
+        modelKey(from, to),
+        ...Object.values(QUALITY_VARIANTS).map(variant => modelKey(from, to, variant)),
+    ].filter(Boolean);
+    const key = candidates.find(k => availableModels.has(k)) || modelKey(from, to);
     return await loadModel(key);
 }
 
+// ============== Jobs ==============
+
+// Long-running requests (/translate/document, /translate/ndjson) register a job whose id is
+// returned in X-Job-Id. POST /jobs/:id/cancel sets its cancelled flag; the request checks it
+// between segments and stops dispatching. A job is forgotten when its response closes.
+// /translate/document only responds when done, so clients that want to cancel it choose the
+// id themselves by sending an X-Job-Id request header.
+const activeJobs = new Map(); // key: job id, value: { id, path, startedAt, cancelled }
+const JOB_ID_PATTERN = /^[\w.:-]{1,128}$/;
+
+// Returns null (after sending a 400) when the client-chosen id is invalid or already running
+function startJob(req, res) {
+    const requested = req.get('X-Job-Id');
+    if (requested !== undefined && (!JOB_ID_PATTERN.test(requested) || activeJobs.has(requested))) {
+        badRequest(res, 'X-Job-Id must be 1-128 letters, digits or ._:- and not already running');
+        return null;
+    }
+    const job = { id: requested ?? crypto.randomUUID(), path: req.path, startedAt: Date.now(), cancelled: false };
+    activeJobs.set(job.id, job);
+    res.once('close', () => activeJobs.delete(job.id));
+    res.set('X-Job-Id', job.id);
+    return job;
+}
+
 // ============== Auth Middleware ==============
 
-function checkAuth(req, res, next) {
-    if (!CONFIG.API_KEY) return next();
+function isPublicPath(requestPath) {
+    return CONFIG.PUBLIC_PATHS.some(entry => entry.endsWith('*')
+        ? requestPath.startsWith(entry.slice(0, -1))
+        : requestPath === entry);
+}
+
+// Accepted keys. API_KEY_FILE / API_KEYS_FILE are read at startup and replace the inline API_KEY,
+// which then never has to appear in the environment (process listings, crash dumps).
+let apiKeys = CONFIG.API_KEY ? [CONFIG.API_KEY] : [];
+
+// Keys from API_KEY_FILE (whole file) and API_KEYS_FILE (one per line, # comments), trimmed.
+// Throws when a file is unreadable or holds no key, so auth never silently turns off.
+function readApiKeyFiles() {
+    const keys = [];
+    if (CONFIG.API_KEY_FILE) {
+        const key = fsSync.readFileSync(CONFIG.API_KEY_FILE, 'utf8').trim();
+        if (!key) throw new Error(`API_KEY_FILE ${CONFIG.API_KEY_FILE} is empty`);
+        keys.push(key);
+    }
+    if (CONFIG.API_KEYS_FILE) {
+        const lines = fsSync.readFileSync(CONFIG.API_KEYS_FILE, 'utf8').split(/\r?\n/)
+            .map(line => line.trim())
+            .filter(line => line && !line.startsWith('#'));
+        if (lines.length === 0) throw new Error(`API_KEYS_FILE ${CONFIG.API_KEYS_FILE} has no keys`);
+        keys.push(...lines);
+    }
+    return keys;
+}
+
+// Key from Authorization: Bearer, ?token= or the API_KEY_HEADER header
+function hasValidApiKey(headers, queryToken) {
+    const headerKey = headers.authorization?.replace('Bearer ', '');
+    // Optional custom header for gateways that strip Authorization
+    const customKey = CONFIG.API_KEY_HEADER ? headers[CONFIG.API_KEY_HEADER] : undefined;
+    return [headerKey, queryToken, customKey].some(key => key !== undefined && apiKeys.includes(key));
+}
 
-    const headerKey = req.headers.authorization?.replace('Bearer ', '');
-    const queryKey = req.query.token;
+function checkAuth(req, res, next) {
+    if (apiKeys.length === 0 || isPublicPath(req.path)) return next();
 
-    if (headerKey !== CONFIG.API_KEY && queryKey !== CONFIG.API_KEY) {
-        return res.status(401).json({ error: 'Invalid or missing API key' });
+    if (!hasValidApiKey(req.headers, req.query.token)) {
+        return sendError(res, new ApiError(401, 'UNAUTHORIZED', 'Invalid or missing API key'));
     }
     next();
 }
 
 // ============== Endpoints ==============
 
+// Retry-After hint while REQUIRED_PAIRS are still missing
+const HEALTH_RETRY_AFTER_SECONDS = 5;
+
 // Health check - returns available models with language info for UI
 app.get('/health', (req, res) => {
-    const models = Array.from(availableModels.entries()).map(([k, v]) => ({
-        key: k,
-        from: v.from,
-        to: v.to,
-    }));
-    res.json({
-        status: 'ok',
+    const models = Array.from(availableModels.entries()).map(([k, v]) => modelSummary(k, v));
+    // Not ready (503) unless every REQUIRED_PAIRS model is registered and its files preloaded without error
+    const missingPairs = CONFIG.REQUIRED_PAIRS.filter(key => {
+        const modelInfo = availableModels.get(canonicalModelKey(key));
+        return !modelInfo || modelInfo.loadError;
+    });
+    const ready = missingPairs.length === 0;
+    const unhealthyPairs = Array.from(availableModels.entries())
+        .filter(([, info]) => info.loadError)
+        .map(([key, info]) => ({ key, error: info.loadError }));
+    if (!ready) res.set('Retry-After', String(HEALTH_RETRY_AFTER_SECONDS));
+    res.status(ready ? 200 : 503).json({
+        status: ready ? 'ok' : 'starting',
         bergamotLoaded: activeModel !== null,
         availableModels: models,
+        ...(CONFIG.REQUIRED_PAIRS.length > 0 && { requiredPairs: CONFIG.REQUIRED_PAIRS, missingPairs }),
+        ...(unhealthyPairs.length > 0 && { unhealthyPairs }),
     });
 });
 
@@ -644,6 +2082,7 @@ app.get('/monitor/stats', (req, res) => {
     const stats = translationLog.getStats();
     res.json({
         ...stats,
+        engineCrashes,
         retentionMinutes: 20,
         serverUptime: process.uptime(),
     });
@@ -655,6 +2094,59 @@ app.post('/monitor/clear', (req, res) => {
     res.json({ success: true });
 });
 
+// Export logged translations as TMX 1.4 for CAT tools, grouped by language pair, at
+// GET /cache/export (also served at its original path, /monitor/export).
+// Truncated log entries are left out since they are not complete translation units.
+function exportTranslationMemory(req, res) {
+    const format = req.query.format || 'tmx';
+    if (format !== 'tmx') return badRequest(res, `Unsupported export format: ${format}`);
+
+    const seen = new Set();
+    const units = translationLog.getRecent(translationLog.maxSize)
+        .filter(e => !e.truncated && e.source && e.translated)
+        .map(e => ({ ...e, from: normalizeLanguageCode(e.from), to: normalizeLanguageCode(e.to) }))
+        .filter(e => {
+            const id = JSON.stringify([e.from, e.to, e.source, e.translated]);
+            if (seen.has(id)) return false;
+            seen.add(id);
+            return true;
+        })
+        .sort((a, b) => `${a.from}-${a.to}`.localeCompare(`${b.from}-${b.to}`) || a.timestamp - b.timestamp);
+
+    const tus = units.map(e => [
+        '    <tu>',
+        `      <prop type="x-pair">${escapeXml(`${e.from}-${e.to}`)}</prop>`,
+        `      <tuv xml:lang="${escapeXml(e.from)}"><seg>${escapeXml(e.source)}</seg></tuv>`,
+        `      <tuv xml:lang="${escapeXml(e.to)}"><seg>${escapeXml(e.translated)}</seg></tuv>`,
+        '    </tu>',
+    ].join('\n'));
+
+    const tmx = [
+        '<?xml version="1.0" encoding="UTF-8"?>',
+        '<tmx version="1.4">',
+        '  <header creationtool="LinguaSpark" creationtoolversion="0.1.0" segtype="paragraph" o-tmf="LinguaSpark" adminlang="en" srclang="*all*" datatype="plaintext"/>',
+        '  <body>',
+        ...tus,
+        '  </body>',
+        '</tmx>',
+        '',
+    ].join('\n');
+
+    res.type('application/x-tmx+xml');
+    res.set('Content-Disposition', 'attachment; filename="linguaspark.tmx"');
+    res.send(tmx);
+}
+
+app.get('/cache/export', exportTranslationMemory);
+app.get('/monitor/export', exportTranslationMemory);
+
+// Recent requests (metadata only, no text), most recent first
+app.get('/debug/requests', (req, res) => {
+    const limit = Math.min(parseInt(req.query.limit) || 50, CONFIG.REQUEST_LOG_SIZE);
+    const requests = requestLog.getRecent(limit);
+    res.json({ requests, count: requests.length });
+});
+
 // Get system stats (CPU, memory)
 app.get('/monitor/system', (req, res) => {
     const memUsage = process.memoryUsage();
@@ -676,54 +2168,693 @@ app.get('/monitor/system', (req, res) => {
 });
 
 // Language detection
-app.post('/detect', checkAuth, (req, res) => {
+app.post('/detect', (req, res) => {
     const { text } = req.body;
-    if (!text) return res.status(400).json({ error: 'Missing text' });
+    if (!text) return badRequest(res, 'Missing text');
+    // An array of texts returns per-item confidence and which detector decided
+    if (Array.isArray(text)) {
+        if (!text.every(t => typeof t === 'string')) return badRequest(res, 'text[] must contain only strings');
+        const limitError = batchLimitError(text);
+        if (limitError) return sendError(res, limitError);
+        return res.json({ results: text.map(detectWithMethod) });
+    }
     res.json({ language: detectLanguage(text) });
 });
 
+// SIGN_RESPONSES: X-Signature: sha256=<hex HMAC of "from\nto\ntext" keyed with RESPONSE_HMAC_KEY>, so
+// clients can check that a translation wasn't altered by an intermediary
+function translationSignature({ text, from, to }) {
+    if (!CONFIG.SIGN_RESPONSES) return null;
+    return 'sha256=' + crypto.createHmac('sha256', CONFIG.RESPONSE_HMAC_KEY).update(`${from}\n${to}\n${text}`).digest('hex');
+}
+
+function signTranslation(res, body) {
+    const signature = translationSignature(body);
+    if (signature) res.set('X-Signature', signature);
+}
+
+// Send a /translate result, honoring Accept: text/plain for shell/curl usage (JSON stays the default)
+function sendTranslation(req, res, body) {
+    signTranslation(res, body);
+    // Protobuf requests get protobuf back unless Accept asks for something else
+    const type = req.accepts(req.protobuf ? [PROTOBUF_TYPE, 'json', 'text'] : ['json', 'text', PROTOBUF_TYPE]);
+    if (type === 'text') {
+        return res.type('text/plain').send(body.text);
+    }
+    if (type === PROTOBUF_TYPE) {
+        return res.type(PROTOBUF_TYPE).send(encodeProtobuf(body, TRANSLATE_RESPONSE_PROTO));
+    }
+    res.json(body);
+}
+
+// Request fields accepted by POST /translate. The handler takes its defaults and per-field checks
+// from this table (readTranslateOptions), and GET /translate/options serves it minus the checks,
+// so the two can't drift apart. `valid` rejects a supplied value with the `invalid` message;
+// `values` rejects anything not listed.
+const TRANSLATE_OPTIONS = [
+    { name: 'text', type: 'string', required: true, description: 'Text to translate', valid: v => typeof v === 'string', invalid: 'text must be a string' },
+    { name: 'from', type: 'string', default: 'auto', description: 'Source language code, or auto to detect' },
+    { name: 'to', type: 'string|array', required: true, description: 'Target language code, or a list tried in order (the first with a model is used)', valid: v => typeof v === 'string' || isValidTargetList(v), invalid: 'to must be a language code or a non-empty list of them' },
+    { name: 'domain', type: 'string', default: null, description: 'Prefer a domain-specific model directory' },
+    { name: 'quality', type: 'string', values: Object.keys(QUALITY_VARIANTS), default: 'fast', description: 'Model quality tier' },
+    { name: 'reverse', type: 'boolean', default: false, description: 'Swap from and to (requires an explicit from)' },
+    { name: 'preserve_entities', type: 'boolean', default: false, description: 'Keep URLs, emails, numbers and currency amounts unchanged' },
+    { name: 'whitespace', type: 'string', values: WHITESPACE_MODES, default: 'preserve', description: 'Whitespace handling' },
+    { name: 'glossary', type: 'object', default: null, description: 'Source term to target string mapping, merged over the server glossary', valid: isValidGlossary, invalid: 'glossary must be an object mapping source terms to target strings' },
+    { name: 'protect_glossary', type: 'boolean', default: true, description: 'Swap glossary terms for placeholders; false lets the model translate them (for verify_glossary QA)' },
+    { name: 'verify_glossary', type: 'boolean', default: false, description: 'Report glossary_ok and missing terms' },
+    { name: 'report_glossary', type: 'boolean', default: false, description: 'Report glossary_applied: each glossary term substituted and how many times' },
+    { name: 'context', type: 'string', default: null, description: 'Preceding text used to disambiguate the translation', valid: v => typeof v === 'string', invalid: 'context must be a string' },
+    { name: 'detect_alternatives', type: 'boolean', default: false, description: 'List other candidate source languages when detection is uncertain' },
+    { name: 'protect_placeholders', type: 'boolean', default: false, description: 'Protect format placeholders and report mismatches' },
+    { name: 'protect_foreign_script', type: 'boolean', default: false, description: 'Keep runs of letters in a script other than the source language\'s (e.g. Latin brand names in Japanese) unchanged' },
+    { name: 'skip_tags', type: 'array', default: null, description: 'HTML elements whose content is left verbatim', valid: isValidSkipTags, invalid: 'skip_tags must be an array of tag names' },
+    { name: 'romanize', type: 'boolean', default: false, description: 'Add a romanized reading of the translation (pinyin, Hepburn kana, Hangul, Cyrillic)' },
+    { name: 'bidi_marks', type: 'boolean', default: false, description: 'Wrap right-to-left output and its embedded URLs/numbers in Unicode bidi isolates' },
+    { name: 'verify_determinism', type: 'boolean', default: false, description: 'Translate twice and report in deterministic whether both outputs matched (doubles the cost)' },
+    { name: 'warmup', type: 'boolean', default: false, description: 'Only load the model for the pair and return {warmed: true}; text may be omitted when from is given' },
+    { name: 'no_content_on_noop', type: 'boolean', default: false, description: 'Answer 204 when there is nothing to translate' },
+];
+
+// Fill in TRANSLATE_OPTIONS defaults for a POST /translate body and check the supplied fields.
+// Returns { options } or { error } (the message for the first invalid field).
+function readTranslateOptions(body) {
+    const options = {};
+    for (const { name, default: fallback = null, values, valid, invalid } of TRANSLATE_OPTIONS) {
+        const value = body[name];
+        if (value === undefined) {
+            options[name] = fallback;
+            continue;
+        }
+        if (values && !values.includes(value)) return { error: `Invalid ${name}: expected one of ${values.join(', ')}` };
+        if (valid && !valid(value)) return { error: invalid };
+        options[name] = value;
+    }
+    return { options };
+}
+
+app.get('/translate/options', (req, res) => {
+    res.json({ options: TRANSLATE_OPTIONS.map(({ valid, invalid, ...option }) => option) });
+});
+
 // Native translate API
-app.post('/translate', checkAuth, async (req, res) => {
-    const { text, from, to } = req.body;
-    if (!text || !to) return res.status(400).json({ error: 'Missing text or to' });
+app.post('/translate', protobufBody, async (req, res) => {
+    if ((!req.body.text && req.body.warmup !== true) || !req.body.to) return badRequest(res, 'Missing text or to');
+    const { options: fields, error } = readTranslateOptions(req.body);
+    if (error) return badRequest(res, error);
+    const { text, domain, quality, reverse, preserve_entities, whitespace, glossary, protect_glossary, verify_glossary, context, detect_alternatives, protect_placeholders, protect_foreign_script, skip_tags, romanize, bidi_marks, warmup, report_glossary, verify_determinism, no_content_on_noop } = fields;
+    let { from, to } = fields;
+    if (warmup === true && !text && (!from || from === 'auto')) {
+        return badRequest(res, 'warmup without text requires an explicit from');
+    }
+    const effectiveGlossary = { ...serverGlossary, ...glossary };
+    if (verify_glossary === true && Object.keys(effectiveGlossary).length === 0) {
+        return badRequest(res, 'verify_glossary requires a glossary');
+    }
 
-    const fromLang = from && from !== 'auto' ? from : detectLanguage(text);
+    // "Translate back": from/to describe the original translation, so swap them
+    if (reverse) {
+        if (!from || from === 'auto') {
+            return badRequest(res, 'reverse requires an explicit from');
+        }
+        if (Array.isArray(to)) return badRequest(res, 'reverse requires a single to');
+        [from, to] = [to, from];
+    }
+
+    const fromLang = resolveSourceLang(from, text);
+
+    // Fallback chain: the first target reachable directly or through pivots wins. `to` in the
+    // response is the target used; target_fallback marks that it wasn't the first.
+    let targetFallback = false;
+    if (Array.isArray(to)) {
+        const target = firstReachableTarget(fromLang, to, CONFIG.MAX_PIVOT_HOPS);
+        if (!target) return sendError(res, unsupportedPairError(`${fromLang}-${to.join(', ')}`));
+        targetFallback = target !== to[0];
+        to = target;
+    }
+    // Without a direct model, translate through pivot languages (pivot_path in the response)
+    const route = findPivotPath(fromLang, to);
+    const pivotRoute = route?.length > 2 ? route : null;
+
+    // Warmup: make the pair's model (a pivot route's first leg) the loaded one and return without
+    // translating, so the client's next real request doesn't pay the cold-start cost
+    if (warmup === true) {
+        try {
+            await (pivotRoute ? getModel(pivotRoute[0], pivotRoute[1], domain, quality) : getModel(fromLang, to, domain, quality));
+            const body = { warmed: true, from: fromLang, to };
+            if (targetFallback) body.target_fallback = true;
+            return res.json(body);
+        } catch (err) {
+            return sendError(res, err);
+        }
+    }
+
+    // No-op (whitespace-only text, same language, already in the target language): echo the
+    // text, or answer 204 for clients that asked for no_content_on_noop / Prefer: return=minimal
+    const noop = (typeof text === 'string' && !text.trim())
+        || normalizeLanguageCode(fromLang) === normalizeLanguageCode(to)
+        || isAlreadyInTarget(text, to);
+    if (noop) {
+        if (no_content_on_noop === true || /\breturn=minimal\b/i.test(req.get('Prefer') || '')) {
+            return res.status(204).end();
+        }
+        const body = { text, from: fromLang, to, skipped: true };
+        if (targetFallback) body.target_fallback = true;
+        return sendTranslation(req, res, body);
+    }
 
     try {
-        const model = await getModel(fromLang, to);
-        const result = doTranslate(model, text);
+        checkPairRateLimit(...(pivotRoute || [fromLang, to]));
+        const options = {
+            preserveEntities: preserve_entities === true,
+            protectPlaceholders: protect_placeholders === true,
+            protectForeignScript: protect_foreign_script === true,
+            skipTags: skip_tags,
+            whitespace, context,
+            // protect_glossary: false leaves the terms to the model, so verify_glossary shows whether it kept them
+            glossary: protect_glossary === false ? null : effectiveGlossary,
+            glossaryApplied: report_glossary === true ? new Map() : null,
+        };
+        const modelsUsed = [];
+        const translate = async (opts, used = null) => {
+            if (pivotRoute) return translateTextVia(pivotRoute, text, opts, domain, quality, used);
+            const model = await getModel(fromLang, to, domain, quality);
+            used?.push(modelDirName(model));
+            return translateText(model, text, opts);
+        };
+        const output = await translate(options, modelsUsed);
+        // verify_determinism: translate a second time and report whether the model agreed with itself
+        let deterministic = null;
+        if (verify_determinism === true) {
+            deterministic = await translate({ ...options, glossaryApplied: null }) === output;
+            if (!deterministic) console.warn(`[Server] Nondeterministic output for ${(pivotRoute || [fromLang, to]).join('>')} for ${text.length} char input (verify_determinism)`);
+        }
+        const { text: translated, guard } = guardOutput(text, output);
+        const result = await postProcess(translated);
         logTranslation(fromLang, to, text, result);
-        res.json({ text: result, from: fromLang, to });
+        const body = { text: bidi_marks === true ? addBidiMarks(result, to) : result, from: fromLang, to };
+        if (targetFallback) body.target_fallback = true;
+        if (pivotRoute) body.pivot_path = pivotRoute;
+        if (guard) body.output_guard = guard;
+        if (deterministic !== null) body.deterministic = deterministic;
+        if (verify_glossary === true) {
+            const missing = missingGlossaryTerms(effectiveGlossary, text, result);
+            body.glossary_ok = missing.length === 0;
+            body.missing = missing;
+        }
+        if (options.glossaryApplied) {
+            body.glossary_applied = Array.from(options.glossaryApplied, ([term, count]) => ({ term, target: effectiveGlossary[term], count }));
+        }
+        if (protect_placeholders === true) {
+            const mismatches = placeholderMismatches(text, result);
+            if (mismatches.length > 0) {
+                console.warn(`[Server] Placeholders lost or duplicated in ${fromLang}-${to} translation: ${mismatches.map(m => m.placeholder).join(', ')}`);
+                body.placeholder_mismatches = mismatches;
+            }
+        }
+        // "Did you mean ...?" support: list the candidates when auto-detection was unsure
+        if (detect_alternatives === true && (!from || from === 'auto')
+            && detectLanguageWithConfidence(text).confidence < UNCERTAIN_DETECTION_CONFIDENCE) {
+            body.from_alternatives = detectionAlternatives(text);
+        }
+        if (romanize === true) {
+            const romanized = romanizeText(result, to);
+            if (romanized !== null) body.romanized = romanized;
+        }
+        if (req.query.debug === 'model') body.model_used = modelsUsed;
+        sendTranslation(req, res, body);
+    } catch (err) {
+        sendError(res, err);
+    }
+});
+
+// Prefix for a segment that failed and was passed through untranslated (on_error: continue),
+// so readers of the document can find it; placed after the segment's leading whitespace
+const PASSTHROUGH_MARKER = '[untranslated] ';
+
+function markPassthrough(chunk, marker) {
+    return chunk.replace(/^\s*/, lead => lead + marker);
+}
+
+// Whole-document translation: split into paragraphs, translate, and reassemble
+// with the original separators so the document structure is kept exactly
+app.post('/translate/document', async (req, res) => {
+    const { text, from, to, domain, quality = 'fast', preserve_entities, protect_foreign_script, skip_tags, segmented, on_error = 'continue', deadline_ms, detect_per_segment, passthrough_marker = PASSTHROUGH_MARKER } = req.body;
+    const deadline = parseDeadline(deadline_ms, Date.now());
+    if (!text || !to) return badRequest(res, 'Missing text or to');
+    if (deadline === null) return badRequest(res, 'deadline_ms must be a positive number');
+    if (typeof passthrough_marker !== 'string') return badRequest(res, 'passthrough_marker must be a string');
+    if (skip_tags !== undefined && !isValidSkipTags(skip_tags)) {
+        return badRequest(res, 'skip_tags must be an array of tag names');
+    }
+    if (!ON_ERROR_MODES.includes(on_error)) {
+        return badRequest(res, `Invalid on_error: expected one of ${ON_ERROR_MODES.join(', ')}`);
+    }
+    if (!Object.hasOwn(QUALITY_VARIANTS, quality)) {
+        return badRequest(res, `Invalid quality: expected one of ${Object.keys(QUALITY_VARIANTS).join(', ')}`);
+    }
+
+    const fromLang = resolveSourceLang(from, text);
+    const perSegment = detect_per_segment === true && (!from || from === 'auto');
+    // Accept: text/event-stream streams a `segment` event per translated paragraph and an `error`
+    // event {index, error} per failing one, then a `done` event carrying the usual JSON body
+    const stream = req.accepts(['json', 'text/event-stream']) === 'text/event-stream';
+    const sendEvent = (event, data) => res.write(`event: ${event}\ndata: ${JSON.stringify(data)}\n\n`);
+    const finish = (body) => {
+        if (!stream) {
+            signTranslation(res, body);
+            return res.json(body);
+        }
+        if (!res.headersSent) res.status(200).type('text/event-stream');
+        const signature = translationSignature(body);
+        sendEvent('done', signature ? { ...body, signature } : body);
+        res.end();
+    };
+
+    if (!perSegment && isAlreadyInTarget(text, to)) {
+        return finish({ text, from: fromLang, to, skipped: true });
+    }
+
+    const job = startJob(req, res);
+    if (!job) return;
+    let failedIndex = null;
+    try {
+        const options = { preserveEntities: preserve_entities === true, protectForeignScript: protect_foreign_script === true, skipTags: skip_tags };
+        const { chunks, separators } = splitDocument(text);
+        res.set('X-Chunk-Count', String(chunks.length));
+        res.set('X-Total-Chars', String(text.length));
+        if (stream) {
+            res.status(200).type('text/event-stream').set('Cache-Control', 'no-cache');
+            res.flushHeaders?.();
+        }
+        // Mixed-language documents: each paragraph is detected on its own (too-short ones keep
+        // the document's language) and translated from that source
+        const sources = chunks.map(chunk => perSegment && chunk.trim().length >= CONFIG.DETECT_MIN_CHARS
+            ? detectLanguage(chunk) : fromLang);
+        // Paragraphs are grouped by source so each model is activated once
+        const groups = new Map();
+        sources.forEach((lang, i) => groups.set(lang, [...(groups.get(lang) || []), i]));
+        // Results are stored by index, so reassembly order never depends on completion order
+        const translated = new Array(chunks.length);
+        const errors = [];
+        const guarded = [];
+        const incomplete = [];
+        let guard = null;
+        for (const [lang, indices] of groups) {
+            if (perSegment && normalizeLanguageCode(lang) === normalizeLanguageCode(to)) {
+                indices.forEach(i => { translated[i] = chunks[i]; });
+                continue;
+            }
+            let model;
+            try {
+                checkPairRateLimit(lang, to);
+                model = await getModel(lang, to, domain, quality);
+            } catch (err) {
+                if (on_error === 'abort' || !perSegment) throw err;
+                indices.forEach(i => {
+                    errors.push({ index: i, error: err.message });
+                    if (stream) sendEvent('error', { index: i, error: err.message });
+                    translated[i] = markPassthrough(chunks[i], passthrough_marker);
+                });
+                continue;
+            }
+            for (const i of indices) {
+                // Yield so a cancel request can be handled between (synchronous) segments
+                await new Promise(resolve => setImmediate(resolve));
+                if (job.cancelled) throw new ApiError(409, 'JOB_CANCELLED', `Job ${job.id} was cancelled`);
+                const chunk = chunks[i];
+                // Out of latency budget: pass the remaining paragraphs through untranslated
+                if (Date.now() >= deadline) {
+                    translated[i] = chunk;
+                    incomplete.push(i);
+                    continue;
+                }
+                try {
+                    let out;
+                    try {
+                        // An engine crash (or another request switching pairs) unloads the model:
+                        // load a fresh instance rather than calling into the deleted one
+                        if (activeModel !== model) model = await getModel(lang, to, domain, quality);
+                        out = translateChunk(model, chunk, options);
+                    } catch (err) {
+                        // One retry before giving up on the segment (and, with abort, on the document)
+                        console.warn(`[Server] Document segment ${i} failed, retrying: ${err.message}`);
+                        if (activeModel !== model) model = await getModel(lang, to, domain, quality);
+                        out = translateChunk(model, chunk, options);
+                    }
+                    translated[i] = out.text;
+                    if (out.guard) {
+                        guard = out.guard;
+                        guarded.push(i);
+                    }
+                    if (stream) sendEvent('segment', { index: i, text: out.text });
+                } catch (err) {
+                    if (on_error === 'abort') {
+                        failedIndex = i;
+                        throw err;
+                    }
+                    errors.push({ index: i, error: err.message });
+                    if (stream) sendEvent('error', { index: i, error: err.message });
+                    translated[i] = markPassthrough(chunk, passthrough_marker);
+                }
+            }
+        }
+        // Groups run out of document order; report indices in order
+        errors.sort((a, b) => a.index - b.index);
+        incomplete.sort((a, b) => a - b);
+        guarded.sort((a, b) => a - b);
+        const result = await postProcess(translated.map((chunk, i) => chunk + separators[i]).join(''));
+
+        logTranslation(fromLang, to, text, result);
+        if (errors.length > 0 && !stream) {
+            res.set('X-Passthrough-Count', String(errors.length));
+            res.set('X-Passthrough-Segments', errors.map(e => e.index).join(','));
+        }
+        const body = { text: result, from: fromLang, to };
+        if (errors.length > 0) body.errors = errors;
+        if (incomplete.length > 0) body.incomplete = incomplete;
+        if (guard) {
+            body.output_guard = guard;
+            body.guarded_segments = guarded;
+        }
+        // Source/target pairs for side-by-side rendering, one per paragraph of the split
+        if (segmented === true) {
+            body.segments = chunks.map((chunk, i) => ({ source: chunk, target: translated[i] }));
+        }
+        if (perSegment) body.segment_sources = sources;
+        finish(body);
     } catch (err) {
-        res.status(500).json({ error: err.message });
+        if (!res.headersSent) return sendError(res, err);
+        // Already streaming: the failure (and, with on_error abort, the segment) ends the stream
+        sendEvent('error', {
+            ...(failedIndex !== null && { index: failedIndex }),
+            error: err.message,
+            code: err instanceof ApiError ? err.code : 'TRANSLATION_FAILED',
+        });
+        res.end();
     }
 });
 
-// Kiss Translator API
-app.post('/kiss', checkAuth, async (req, res) => {
-    const { text, from, to } = req.body;
-    if (!text || !to) return res.status(400).json({ error: 'Missing text or to' });
+// Incremental document update: paragraphs of the new source that also appear in the previous
+// source reuse their previous translation; only new or edited paragraphs are translated.
+// previous_source and previous_translation must split into the same number of paragraphs.
+app.post('/translate/diff', async (req, res) => {
+    const { previous_source, previous_translation, source, from, to, domain, quality = 'fast' } = req.body;
+    if (typeof source !== 'string' || !source || !to) return badRequest(res, 'Missing source or to');
+    if (typeof previous_source !== 'string' || typeof previous_translation !== 'string') {
+        return badRequest(res, 'previous_source and previous_translation must be strings');
+    }
+    if (!Object.hasOwn(QUALITY_VARIANTS, quality)) {
+        return badRequest(res, `Invalid quality: expected one of ${Object.keys(QUALITY_VARIANTS).join(', ')}`);
+    }
+    const previous = splitDocument(previous_source).chunks;
+    const previousTranslated = splitDocument(previous_translation).chunks;
+    if (previous.length !== previousTranslated.length) {
+        return badRequest(res, `previous_source has ${previous.length} paragraphs but previous_translation has ${previousTranslated.length}`);
+    }
 
-    const fromLang = from && from !== 'auto' ? from : detectLanguage(text);
+    // Translation memory from the previous version: whole paragraphs, and their sentences where
+    // the source and translated paragraph split into the same number of sentences
+    const paragraphMemory = new Map();
+    const sentenceMemory = new Map();
+    previous.forEach((chunk, i) => {
+        paragraphMemory.set(chunk.trim(), previousTranslated[i]);
+        const sources = splitSentences(chunk);
+        const targets = splitSentences(previousTranslated[i]);
+        if (sources.length !== targets.length) return;
+        sources.forEach((sentence, j) => sentenceMemory.set(sentence.body, targets[j].body));
+    });
 
+    // Sentences are numbered in document order; only those found in neither memory are translated
+    const { chunks, separators } = splitDocument(source);
+    const paragraphs = chunks.map(chunk => ({ chunk, sentences: splitSentences(chunk), reused: paragraphMemory.get(chunk.trim()) }));
+    const changed = [];
+    let sentenceCount = 0;
+    for (const { sentences, reused } of paragraphs) {
+        for (const sentence of sentences) {
+            sentence.index = sentenceCount++;
+            if (reused === undefined && !sentenceMemory.has(sentence.body)) changed.push(sentence);
+        }
+    }
+    const fromLang = resolveSourceLang(from, changed.map(sentence => sentence.body).join('\n') || source);
     try {
+        if (changed.length > 0) {
+            checkPairRateLimit(fromLang, to);
+            const model = await getModel(fromLang, to, domain, quality);
+            for (const sentence of changed) {
+                sentence.translation = translateChunk(model, sentence.body).text;
+                logTranslation(fromLang, to, sentence.body, sentence.translation);
+            }
+        }
+        const translated = paragraphs.map(({ chunk, sentences, reused }) => {
+            if (!chunk.trim()) return chunk;
+            if (reused !== undefined) return reused;
+            return sentences.map(s => s.lead + (s.translation ?? sentenceMemory.get(s.body)) + s.trail).join('');
+        });
+        const result = translated.map((chunk, i) => chunk + separators[i]).join('');
+        res.set('X-Reused-Segments', String(sentenceCount - changed.length));
+        const body = { text: result, from: fromLang, to, translated_segments: changed.map(sentence => sentence.index) };
+        signTranslation(res, body);
+        res.json(body);
+    } catch (err) {
+        sendError(res, err);
+    }
+});
+
+// Segmentation preview for /translate/document: how the text would be split and how many
+// model calls it implies, without translating. Sentence counts are an estimate; Bergamot
+// does its own sentence splitting inside each paragraph.
+app.post('/translate/segment', (req, res) => {
+    const { text } = req.body;
+    if (!text || typeof text !== 'string') return badRequest(res, 'Missing text');
+
+    const { chunks, separators } = splitDocument(text);
+    const segments = chunks.map((chunk, index) => ({
+        index,
+        text: chunk,
+        separator: separators[index],
+        chars: chunk.length,
+        sentences: splitSentences(chunk).length,
+        translated: chunk.trim().length > 0,
+    }));
+    res.json({
+        segments,
+        count: segments.length,
+        translation_calls: segments.filter(s => s.translated).length,
+        total_chars: text.length,
+    });
+});
+
+// Streaming batch translation as NDJSON: one {"index", "text"} (or {"index", "error"}) line per
+// segment as soon as it is translated, then a {"done": true, "count", "errors"} summary line.
+// With on_error abort the first error line is followed by {"done": true, "aborted": true, ...}.
+app.post('/translate/ndjson', async (req, res) => {
+    const { texts, from, to, domain, on_error = 'abort' } = req.body;
+    if (!Array.isArray(texts) || !texts.every(t => typeof t === 'string') || !to) {
+        return badRequest(res, 'Missing texts[] or to');
+    }
+    if (!ON_ERROR_MODES.includes(on_error)) {
+        return badRequest(res, `Invalid on_error: expected one of ${ON_ERROR_MODES.join(', ')}`);
+    }
+    const limitError = batchLimitError(texts);
+    if (limitError) return sendError(res, limitError);
+    const job = startJob(req, res);
+    if (!job) return;
+
+    const fromLang = resolveSourceLang(from, texts.slice(0, 20).join('\n'));
+    let model;
+    try {
+        checkPairRateLimit(fromLang, to);
+        model = await getModel(fromLang, to, domain);
+    } catch (err) {
+        return sendError(res, err);
+    }
+
+    let closed = false;
+    res.on('close', () => { closed = true; });
+    res.status(200).type('application/x-ndjson');
+    res.flushHeaders?.();
+
+    let errors = 0;
+    for (const [index, text] of texts.entries()) {
+        if (closed) return;
+        if (job.cancelled) {
+            return res.end(JSON.stringify({ done: true, cancelled: true, count: index, errors, from: fromLang, to }) + '\n');
+        }
+        try {
+            // Reload after an engine crash unloaded the model (see /translate/document)
+            if (activeModel !== model) model = await getModel(fromLang, to, domain);
+            const result = translateText(model, text);
+            logTranslation(fromLang, to, text, result);
+            res.write(JSON.stringify({ index, text: result }) + '\n');
+        } catch (err) {
+            errors++;
+            res.write(JSON.stringify({ index, error: err.message }) + '\n');
+            if (on_error === 'abort') {
+                return res.end(JSON.stringify({ done: true, aborted: true, count: index + 1, errors, from: fromLang, to }) + '\n');
+            }
+        }
+        // Yield so the line is flushed before the next (synchronous) translation starts
+        await new Promise(resolve => setImmediate(resolve));
+    }
+    res.end(JSON.stringify({ done: true, count: texts.length, errors, from: fromLang, to }) + '\n');
+});
+
+// Cancel a running /translate/document or /translate/ndjson request by its X-Job-Id
+app.post('/jobs/:id/cancel', (req, res) => {
+    const job = activeJobs.get(req.params.id);
+    if (!job) return sendError(res, new ApiError(404, 'NOT_FOUND', `Job not found: ${req.params.id}`));
+    job.cancelled = true;
+    res.json({ id: job.id, path: job.path, cancelled: true });
+});
+
+// CSV translation: only the listed columns (0-based indices or header names) are translated;
+// every other cell, the header row, quoting and line endings are kept as-is.
+// Accepts JSON { csv, columns, from?, to, header?, delimiter? } or a text/csv body with the
+// same fields in the query string (columns comma-separated).
+app.post('/translate/csv', express.text({ type: 'text/csv', limit: '10mb' }), async (req, res) => {
+    const csvBody = typeof req.body === 'string';
+    const params = csvBody ? req.query : req.body;
+    const csv = csvBody ? req.body : params.csv;
+    const { from, to, delimiter = ',' } = params;
+    const columns = typeof params.columns === 'string' ? params.columns.split(',') : params.columns;
+    const header = params.header === undefined || params.header === true || params.header === 'true' || params.header === '1';
+    if (!csv || !to) return badRequest(res, 'Missing csv or to');
+    if (!Array.isArray(columns) || columns.length === 0) return badRequest(res, 'Missing columns');
+    if (typeof delimiter !== 'string' || delimiter.length !== 1 || /["\r\n]/.test(delimiter)) {
+        return badRequest(res, 'delimiter must be a single character other than a quote or newline');
+    }
+
+    let rows;
+    try {
+        rows = parseCsv(csv, delimiter);
+    } catch (err) {
+        return badRequest(res, `Invalid CSV: ${err.message}`);
+    }
+
+    const names = header ? rows[0].cells.map(cell => cell.value.trim()) : [];
+    const indices = [];
+    for (const column of columns) {
+        const index = /^\d+$/.test(String(column)) ? Number(column) : names.indexOf(String(column).trim());
+        if (index < 0) return badRequest(res, `Unknown column: ${column}`);
+        indices.push(index);
+    }
+
+    // Each distinct cell value is translated once
+    const targets = rows.slice(header ? 1 : 0)
+        .flatMap(row => indices.map(index => row.cells[index]))
+        .filter(cell => cell && cell.value.trim());
+    const unique = [...new Set(targets.map(cell => cell.value))];
+    const limitError = batchLimitError(unique);
+    if (limitError) return sendError(res, limitError);
+    if (unique.length === 0) return res.type('text/csv').send(csv);
+
+    const fromLang = resolveSourceLang(from, unique.slice(0, 20).join('\n'));
+    try {
+        checkPairRateLimit(fromLang, to);
+        const model = await getModel(fromLang, to);
+        const translations = new Map();
+        for (const value of unique) {
+            const result = translateText(model, value);
+            logTranslation(fromLang, to, value, result);
+            translations.set(value, result);
+        }
+        for (const cell of targets) {
+            cell.raw = quoteCsvCell(translations.get(cell.value), delimiter, cell.raw.startsWith('"'));
+        }
+        res.set('X-Translated-Cells', String(targets.length));
+        res.type('text/csv').send(serializeCsv(rows, delimiter));
+    } catch (err) {
+        sendError(res, err);
+    }
+});
+
+// Key-value localization files (.properties, .env): only the values are translated. Send the
+// file as text/plain (from/to in the query) or JSON {kv, from, to}, where kv is either the file
+// text or a flat object of key -> string.
+app.post('/translate/kv', express.text({ type: 'text/plain', limit: '10mb' }), async (req, res) => {
+    const textBody = typeof req.body === 'string';
+    const params = textBody ? req.query : req.body;
+    const kv = textBody ? req.body : params.kv;
+    const { from, to } = params;
+    if (!kv || !to) return badRequest(res, 'Missing kv or to');
+    const isObject = typeof kv === 'object' && !Array.isArray(kv);
+    if (!isObject && typeof kv !== 'string') {
+        return badRequest(res, 'kv must be key=value text or an object of strings');
+    }
+
+    const entries = isObject
+        ? Object.entries(kv).map(([key, value]) => ({ key, value }))
+        : parseKeyValues(kv);
+    // Each distinct value is translated once
+    const targets = entries.filter(entry => typeof entry.value === 'string' && entry.value.trim());
+    const unique = [...new Set(targets.map(entry => entry.value))];
+    const limitError = batchLimitError(unique);
+    if (limitError) return sendError(res, limitError);
+
+    const fromLang = resolveSourceLang(from, unique.slice(0, 20).join('\n'));
+    try {
+        const translations = new Map();
+        if (unique.length > 0) {
+            checkPairRateLimit(fromLang, to);
+            const model = await getModel(fromLang, to);
+            for (const value of unique) {
+                const result = translateText(model, value);
+                logTranslation(fromLang, to, value, result);
+                translations.set(value, result);
+            }
+        }
+        res.set('X-Translated-Values', String(targets.length));
+        if (isObject) {
+            const out = {};
+            for (const { key, value } of entries) out[key] = translations.get(value) ?? value;
+            return res.json({ kv: out, from: fromLang, to });
+        }
+        const text = entries.map(entry => translations.has(entry.value)
+            ? entry.prefix + formatKeyValue(translations.get(entry.value), entry.quote, entry.ascii) + entry.eol
+            : entry.raw).join('');
+        res.type('text/plain').send(text);
+    } catch (err) {
+        sendError(res, err);
+    }
+});
+
+// Kiss Translator API (its "Custom" translator): { text, from, to } in, { text, from, to } out.
+// source_lang/target_lang are accepted as aliases; a missing or "auto" source is detected and the
+// detected language is returned in `from`. Text already in the target language is echoed back.
+app.post('/kiss', async (req, res) => {
+    const { text } = req.body;
+    const from = req.body.from ?? req.body.source_lang;
+    const to = req.body.to ?? req.body.target_lang;
+    if (typeof text !== 'string' || !text || !to) return badRequest(res, 'Missing text or to');
+
+    const fromLang = resolveSourceLang(from, text);
+    if (normalizeLanguageCode(fromLang) === normalizeLanguageCode(to)) {
+        return res.json({ text, from: fromLang, to });
+    }
+
+    try {
+        checkPairRateLimit(fromLang, to);
         const model = await getModel(fromLang, to);
         const result = doTranslate(model, text);
         logTranslation(fromLang, to, text, result);
         res.json({ text: result, from: fromLang, to });
     } catch (err) {
-        res.status(500).json({ error: err.message });
+        sendError(res, err);
     }
 });
 
 // Immersive Translate API (batch)
-app.post('/imme', checkAuth, async (req, res) => {
+app.post('/imme', async (req, res) => {
     const { source_lang, target_lang, text_list } = req.body;
-    if (!target_lang || !text_list) return res.status(400).json({ error: 'Missing target_lang or text_list' });
+    if (!target_lang || !text_list) return badRequest(res, 'Missing target_lang or text_list');
+    const limitError = batchLimitError(text_list);
+    if (limitError) return sendError(res, limitError);
 
-    const fromLang = source_lang && source_lang !== 'auto' ? source_lang : detectLanguage(text_list[0] || '');
+    const fromLang = resolveSourceLang(source_lang, text_list[0] || '');
 
     try {
+        checkPairRateLimit(fromLang, target_lang);
         const model = await getModel(fromLang, target_lang);
         const translations = [];
         for (const text of text_list) {
@@ -737,14 +2868,14 @@ app.post('/imme', checkAuth, async (req, res) => {
 
         res.json({ translations });
     } catch (err) {
-        res.status(500).json({ error: err.message });
+        sendError(res, err);
     }
 });
 
 // HCFY API
-app.post('/hcfy', checkAuth, async (req, res) => {
+app.post('/hcfy', async (req, res) => {
     const { text, source, destination } = req.body;
-    if (!text || !destination) return res.status(400).json({ error: 'Missing text or destination' });
+    if (!text || !destination) return badRequest(res, 'Missing text or destination');
 
     const srcName = source || 'english';
     const srcLang = convertLangName(srcName);
@@ -772,26 +2903,28 @@ app.post('/hcfy', checkAuth, async (req, res) => {
     }
 
     try {
+        checkPairRateLimit(srcIso, tgtIso);
         const model = await getModel(srcIso, tgtIso);
         const result = doTranslate(model, text);
         logTranslation(srcIso, tgtIso, text, result);
         res.json({ text, from: srcName, to: destination[0], result: [result] });
     } catch (err) {
-        res.status(500).json({ error: err.message });
+        sendError(res, err);
     }
 });
 
 // DeepLX API
-app.post('/deeplx', checkAuth, async (req, res) => {
+app.post('/deeplx', async (req, res) => {
     const { text, source_lang, target_lang } = req.body;
     if (!text || !source_lang || !target_lang) {
-        return res.status(400).json({ error: 'Missing required fields' });
+        return badRequest(res, 'Missing required fields');
     }
 
     const fromLang = source_lang.toLowerCase();
     const toLang = target_lang.toLowerCase();
 
     try {
+        checkPairRateLimit(fromLang, toLang);
         const model = await getModel(fromLang, toLang);
         const result = doTranslate(model, text);
         logTranslation(fromLang, toLang, text, result);
@@ -805,30 +2938,49 @@ app.post('/deeplx', checkAuth, async (req, res) => {
             method: 'Free',
         });
     } catch (err) {
-        res.status(500).json({ error: err.message });
+        sendError(res, err);
     }
 });
 
 // MTranServer compatible API - Single translation
 // POST /translate_mtranserver
-// Input: { from: string, to: string, text: string, html?: boolean }
-// Output: { result: string }
+// Input: { from: string, to: string | string[], text: string, html?: boolean }
+// Output: { result: string } (plus `to`, the target used, when `to` is a fallback list)
 app.post('/translate_mtranserver', async (req, res) => {
     const { from, to, text, html } = req.body;
     if (!from || !to || !text) {
-        return res.status(400).json({ error: 'Missing required fields: from, to, text' });
+        return badRequest(res, 'Missing required fields: from, to, text');
+    }
+    if (typeof to !== 'string' && !isValidTargetList(to)) {
+        return badRequest(res, 'to must be a language code or a non-empty list of them');
     }
 
     try {
         const normalizedFrom = normalizeLanguageCode(from);
-        const normalizedTo = normalizeLanguageCode(to);
+        // Fallback chain: the first target reachable directly or through pivots
+        const target = Array.isArray(to) ? firstReachableTarget(normalizedFrom, to.map(normalizeLanguageCode), CONFIG.MAX_PIVOT_HOPS) : to;
+        if (!target) throw unsupportedPairError(`${normalizedFrom}-${to.join(', ')}`);
+        const normalizedTo = normalizeLanguageCode(target);
+        // Same language: nothing to translate, so no route and no model
+        if (normalizedFrom === normalizedTo) {
+            const body = { result: text };
+            if (Array.isArray(to)) body.to = normalizedTo;
+            return res.json(body);
+        }
+        const route = findPivotPath(normalizedFrom, normalizedTo);
+        if (route) checkPairRateLimit(...route);
 
-        const result = await translateWithPivot(normalizedFrom, normalizedTo, text, html || false);
+        const modelsUsed = req.query.debug === 'model' ? [] : null;
+        const result = await translateWithPivot(normalizedFrom, normalizedTo, text, html || false, modelsUsed);
         logTranslation(normalizedFrom, normalizedTo, text, result);
-        res.json({ result });
+        const body = { result };
+        if (Array.isArray(to)) body.to = normalizedTo;
+        if (route?.length > 2) body.pivot_path = route;
+        if (modelsUsed) body.model_used = modelsUsed;
+        res.json(body);
     } catch (err) {
         console.error('[Server] MTranServer translate error:', err);
-        res.status(500).json({ error: err.message });
+        sendError(res, err);
     }
 });
 
@@ -837,51 +2989,223 @@ app.post('/translate_mtranserver', async (req, res) => {
 // Input: { from: string, to: string, texts: string[], html?: boolean }
 // Output: { results: string[] }
 app.post('/translate_mtranserver/batch', async (req, res) => {
-    const { from, to, texts, html } = req.body;
+    const { from, to, texts, html, on_error = 'abort', deadline_ms } = req.body;
+    const deadline = parseDeadline(deadline_ms, Date.now());
     if (!from || !to || !texts || !Array.isArray(texts)) {
-        return res.status(400).json({ error: 'Missing required fields: from, to, texts[]' });
+        return badRequest(res, 'Missing required fields: from, to, texts[]');
+    }
+    if (deadline === null) return badRequest(res, 'deadline_ms must be a positive number');
+    if (!ON_ERROR_MODES.includes(on_error)) {
+        return badRequest(res, `Invalid on_error: expected one of ${ON_ERROR_MODES.join(', ')}`);
     }
+    const limitError = batchLimitError(texts);
+    if (limitError) return sendError(res, limitError);
 
     try {
         const normalizedFrom = normalizeLanguageCode(from);
         const normalizedTo = normalizeLanguageCode(to);
+        // Same language: echo the batch without routing, so no pair's rate limit is charged
+        if (normalizedFrom === normalizedTo) return res.json({ results: texts });
+        const route = findPivotPath(normalizedFrom, normalizedTo);
+        if (route) checkPairRateLimit(...route);
 
         const results = [];
-        for (const text of texts) {
-            const result = await translateWithPivot(normalizedFrom, normalizedTo, text, html || false);
-            logTranslation(normalizedFrom, normalizedTo, text, result);
-            results.push(result);
+        const errors = [];
+        const incomplete = [];
+        for (const [index, text] of texts.entries()) {
+            if (Date.now() >= deadline) {
+                incomplete.push(index);
+                results.push(text);
+                continue;
+            }
+            try {
+                const result = await translateWithPivot(normalizedFrom, normalizedTo, text, html || false);
+                logTranslation(normalizedFrom, normalizedTo, text, result);
+                results.push(result);
+            } catch (err) {
+                if (on_error === 'abort') throw err;
+                errors.push({ index, error: err.message });
+                results.push(text);
+            }
         }
-        res.json({ results });
+        const body = { results };
+        if (route?.length > 2) body.pivot_path = route;
+        if (errors.length > 0) body.errors = errors;
+        if (incomplete.length > 0) body.incomplete = incomplete;
+        res.json(body);
     } catch (err) {
         console.error('[Server] MTranServer batch translate error:', err);
-        res.status(500).json({ error: err.message });
+        sendError(res, err);
     }
 });
 
+// ============== Status WebSocket ==============
+
+// GET /ws/status (WebSocket, always requires API_KEY when set; browsers pass ?token=): pushes a
+// statusSnapshot() frame every STATUS_PUSH_INTERVAL_MS, and sooner when the active model, the
+// models being loaded or the set of unhealthy pairs changes. Server-to-client only.
+const STATUS_PUSH_INTERVAL_MS = 5000;
+const STATUS_CHECK_INTERVAL_MS = 1000;
+const WEBSOCKET_GUID = '258EAFA5-E914-47DA-95CA-C5AB0DC85B11';
+// Largest client frame payload accepted; clients only need to send close and ping
+const MAX_CLIENT_FRAME_BYTES = 4096;
+
+function statusSnapshot() {
+    const now = Date.now();
+    const recent = requestLog.getRecent(Infinity).filter(entry => now - entry.timestamp < 60000);
+    const lookups = detectionCacheHits + detectionCacheMisses;
+    return {
+        timestamp: now,
+        activeModel: activeModel?.key ?? null,
+        loading: [...loadingLocks.keys()],
+        inFlight: inFlightRequests,
+        models: Array.from(availableModels.entries()).map(([key, info]) => ({
+            key,
+            healthy: !info.loadError,
+            ...(info.loadError && { error: info.loadError }),
+        })),
+        requestsLastMinute: recent.length,
+        errorRateLastMinute: recent.length ? recent.filter(entry => entry.status >= 500).length / recent.length : 0,
+        engineCrashes,
+        detectCacheHitRatio: lookups ? detectionCacheHits / lookups : null,
+    };
+}
+
+// Single unmasked text (0x1), close (0x8) or pong (0xA) frame
+function webSocketFrame(opcode, payload = Buffer.alloc(0)) {
+    const data = Buffer.isBuffer(payload) ? payload : Buffer.from(payload);
+    let header;
+    if (data.length < 126) {
+        header = Buffer.from([0x80 | opcode, data.length]);
+    } else if (data.length < 65536) {
+        header = Buffer.alloc(4);
+        header.writeUInt16BE(data.length, 2);
+        header[1] = 126;
+    } else {
+        header = Buffer.alloc(10);
+        header.writeBigUInt64BE(BigInt(data.length), 2);
+        header[1] = 127;
+    }
+    header[0] = 0x80 | opcode;
+    return Buffer.concat([header, data]);
+}
+
+// First complete frame in `buffer` as { opcode, payload (unmasked), length (bytes consumed) },
+// or null while more bytes are needed. A frame can arrive split across 'data' events, and
+// several frames can arrive in one.
+function readWebSocketFrame(buffer) {
+    if (buffer.length < 2) return null;
+    const masked = (buffer[1] & 0x80) !== 0;
+    let length = buffer[1] & 0x7f;
+    let offset = 2;
+    if (length === 126) {
+        if (buffer.length < 4) return null;
+        length = buffer.readUInt16BE(2);
+        offset = 4;
+    } else if (length === 127) {
+        if (buffer.length < 10) return null;
+        const length64 = buffer.readBigUInt64BE(2);
+        length = length64 > BigInt(MAX_CLIENT_FRAME_BYTES) ? Infinity : Number(length64);
+        offset = 10;
+    }
+    if (length > MAX_CLIENT_FRAME_BYTES) throw new Error(`WebSocket frame over ${MAX_CLIENT_FRAME_BYTES} bytes`);
+    const mask = masked ? buffer.subarray(offset, offset + 4) : null;
+    if (masked) offset += 4;
+    if (buffer.length < offset + length) return null;
+    const payload = Buffer.from(buffer.subarray(offset, offset + length));
+    if (mask) payload.forEach((byte, i) => { payload[i] = byte ^ mask[i % 4]; });
+    return { opcode: buffer[0] & 0x0f, payload, length: offset + length };
+}
+
+function handleStatusUpgrade(req, socket, head) {
+    const url = new URL(req.url, 'http://localhost');
+    const key = req.headers['sec-websocket-key'];
+    if (url.pathname !== '/ws/status' || !key || req.headers.upgrade?.toLowerCase() !== 'websocket') {
+        socket.end('HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\n');
+        return;
+    }
+    if (apiKeys.length > 0 && !hasValidApiKey(req.headers, url.searchParams.get('token'))) {
+        socket.end('HTTP/1.1 401 Unauthorized\r\nConnection: close\r\n\r\n');
+        return;
+    }
+
+    const accept = crypto.createHash('sha1').update(key + WEBSOCKET_GUID).digest('base64');
+    socket.write(`HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: ${accept}\r\n\r\n`);
+
+    let lastState = null;
+    let lastPush = 0;
+    const push = () => {
+        const snapshot = statusSnapshot();
+        const state = JSON.stringify([snapshot.activeModel, snapshot.loading, snapshot.models.filter(m => !m.healthy).map(m => m.key)]);
+        if (state === lastState && Date.now() - lastPush < STATUS_PUSH_INTERVAL_MS) return;
+        lastState = state;
+        lastPush = Date.now();
+        socket.write(webSocketFrame(0x1, JSON.stringify(snapshot)));
+    };
+    push();
+    const timer = setInterval(push, STATUS_CHECK_INTERVAL_MS);
+    socket.on('close', () => clearInterval(timer));
+    socket.on('error', () => clearInterval(timer));
+
+    // Only close and ping need an answer; anything else the client sends is skipped
+    let pending = Buffer.alloc(0);
+    const receive = (chunk) => {
+        pending = Buffer.concat([pending, chunk]);
+        for (;;) {
+            let frame;
+            try {
+                frame = readWebSocketFrame(pending);
+            } catch {
+                socket.destroy();
+                return;
+            }
+            if (!frame) return;
+            pending = pending.subarray(frame.length);
+            if (frame.opcode === 0x8) {
+                clearInterval(timer);
+                socket.off('data', receive);
+                socket.end(webSocketFrame(0x8));
+                return;
+            }
+            if (frame.opcode === 0x9) socket.write(webSocketFrame(0xa, frame.payload));
+        }
+    };
+    socket.on('data', receive);
+    if (head?.length) receive(head);
+}
+
 // ============== Model Management ==============
 
+// Registry entry as listed by /models and /health
+function modelSummary(key, modelInfo) {
+    const aliases = aliasesOf(key);
+    return {
+        key,
+        from: modelInfo.from,
+        to: modelInfo.to,
+        ...(modelInfo.domain && { domain: modelInfo.domain }),
+        ...(aliases.length > 0 && { aliases }),
+    };
+}
+
 // Get list of available models (public - no auth required for UI)
 app.get('/models', (req, res) => {
-    const models = Array.from(availableModels.entries()).map(([k, v]) => ({
-        key: k,
-        from: v.from,
-        to: v.to,
-    }));
+    const models = Array.from(availableModels.entries()).map(([k, v]) => modelSummary(k, v));
     res.json({ models });
 });
 
 // Register a model for on-demand loading
-app.post('/models/load', checkAuth, async (req, res) => {
-    const { from, to, modelDir } = req.body;
-    if (!from || !to) return res.status(400).json({ error: 'Missing from or to' });
+app.post('/models/load', async (req, res) => {
+    const { from, to, domain = null, modelDir } = req.body;
+    if (!from || !to) return badRequest(res, 'Missing from or to');
 
-    const key = `${from}-${to}`;
+    const key = modelKey(from, to, domain);
     if (availableModels.has(key)) {
         return res.json({ success: true, key, from, to, alreadyRegistered: true });
     }
 
-    const dir = normalizePath(modelDir || path.join(CONFIG.MODELS_DIR, key));
+    const dirName = domain ? `${from}-${to}-${domain}` : key;
+    const dir = normalizePath(modelDir || path.join(CONFIG.MODELS_DIR, dirName));
 
     console.log(`[Server] Registering model: ${key} from ${dir}`);
 
@@ -890,47 +3214,227 @@ app.post('/models/load', checkAuth, async (req, res) => {
         const buffers = await loadModelFiles(dir);
 
         // Register for on-demand loading
-        availableModels.set(key, { dir, from, to, buffers });
+        availableModels.set(key, { dir, from, to, domain, buffers });
+        touchModelBuffers(key);
         console.log(`[Server] Model registered: ${key}`);
 
         res.json({ success: true, key, from, to, message: 'Model registered for on-demand loading' });
     } catch (err) {
         console.error(`[Server] Failed to register model ${key}:`, err);
-        res.status(500).json({ error: err.message });
+        sendError(res, err);
     }
 });
 
+// Drop cached model buffers (and the active model) so swapped model files are re-read from disk
+// Body: { from?, to? } to clear a single pair
+app.post('/cache/clear', (req, res) => {
+    const { from, to } = req.body || {};
+    if ((from && !to) || (!from && to)) {
+        return badRequest(res, 'Provide both from and to, or neither');
+    }
+
+    const key = from ? modelKey(from, to) : null;
+    let cleared = 0;
+    for (const [modelKeyName, modelInfo] of availableModels) {
+        // A pair filter also covers that pair's domain variants
+        if (key && (modelInfo.from !== from || modelInfo.to !== to)) continue;
+        bufferUsage.delete(modelKeyName);
+        if (modelInfo.buffers) {
+            modelInfo.buffers = null;
+            cleared++;
+        }
+    }
+    if (activeModel && (!key || (activeModel.from === from && activeModel.to === to))) {
+        unloadActiveModel();
+    }
+
+    console.log(`[Server] Cleared cached buffers for ${cleared} model(s)${key ? ` (${key})` : ''}`);
+    res.json({ success: true, cleared });
+});
+
 // ============== Initialization ==============
 
+// BCP-47 script ("Hans") and region ("TW", "419") subtags after the pair qualify the target
+// language rather than naming a domain. Case matters: domains are lower-case ("hq", "fast").
+const SCRIPT_OR_REGION_SUBTAG = /^(?:[A-Z][a-z]{3}|[A-Z]{2}|\d{3})$/;
+
+// Parse a model directory name (supports "enzh", "en-zh", "enja", "en-ja")
+// plus a domain suffix: "enzh-medical", "en-zh-medical". Script and region subtags are
+// skipped, so "en-zh-Hans" is en-zh and "en-zh-Hans-legal" its legal domain.
+// Returns null for invalid names.
+function parseModelDirName(name) {
+    const parts = name.split('-');
+    let from, to, rest;
+
+    if (parts.length >= 2 && /^[a-z]{4}$/.test(parts[0])) {
+        [from, to, rest] = [parts[0].slice(0, 2), parts[0].slice(2, 4), parts.slice(1)];
+    } else if (parts.length >= 2) {
+        [from, to, rest] = [parts[0], parts[1], parts.slice(2)];
+    } else if (name.length >= 4) {
+        return { from: name.slice(0, 2), to: name.slice(2, 4), domain: null };
+    } else {
+        return null;
+    }
+    while (rest.length > 0 && SCRIPT_OR_REGION_SUBTAG.test(rest[0])) rest.shift();
+    return { from, to, domain: rest.length > 0 ? rest.join('-') : null };
+}
+
+// Minimal ZIP reader (stored and deflated entries, no ZIP64). Entries are written flat
+// by basename, since model files live at the top level of a model directory.
+async function extractZip(zipPath, destDir) {
+    const data = await fs.readFile(zipPath);
+
+    // Find the end-of-central-directory record (it may be followed by a comment)
+    let eocd = -1;
+    for (let i = data.length - 22; i >= Math.max(0, data.length - 65557); i--) {
+        if (data.readUInt32LE(i) === 0x06054b50) {
+            eocd = i;
+            break;
+        }
+    }
+    if (eocd < 0) throw new Error(`Not a zip file: ${zipPath}`);
+
+    const count = data.readUInt16LE(eocd + 10);
+    let offset = data.readUInt32LE(eocd + 16);
+
+    await fs.mkdir(destDir, { recursive: true });
+    for (let i = 0; i < count; i++) {
+        if (data.readUInt32LE(offset) !== 0x02014b50) throw new Error(`Corrupt zip central directory: ${zipPath}`);
+        const method = data.readUInt16LE(offset + 10);
+        const compressedSize = data.readUInt32LE(offset + 20);
+        const nameLen = data.readUInt16LE(offset + 28);
+        const extraLen = data.readUInt16LE(offset + 30);
+        const commentLen = data.readUInt16LE(offset + 32);
+        const localOffset = data.readUInt32LE(offset + 42);
+        const name = data.toString('utf-8', offset + 46, offset + 46 + nameLen);
+        offset += 46 + nameLen + extraLen + commentLen;
+
+        if (name.endsWith('/')) continue; // directory entry
+        if (compressedSize === 0xffffffff) throw new Error(`ZIP64 archives are not supported: ${zipPath}`);
+
+        const localNameLen = data.readUInt16LE(localOffset + 26);
+        const localExtraLen = data.readUInt16LE(localOffset + 28);
+        const start = localOffset + 30 + localNameLen + localExtraLen;
+        const raw = data.subarray(start, start + compressedSize);
+
+        let content;
+        if (method === 0) content = raw;
+        else if (method === 8) content = zlib.inflateRawSync(raw);
+        else throw new Error(`Unsupported zip compression method ${method} for ${name}`);
+
+        await fs.writeFile(path.join(destDir, path.basename(name)), content);
+    }
+}
+
+// Extract a zipped model to the cache directory, skipping extraction when the zip is unchanged
+async function extractModelZip(zipPath, name) {
+    const destDir = path.join(CONFIG.ZIP_CACHE_DIR, name);
+    const stat = await fs.stat(zipPath);
+    const stamp = `${stat.size}:${stat.mtimeMs}`;
+    const stampFile = path.join(destDir, '.source');
+
+    try {
+        if (await fs.readFile(stampFile, 'utf-8') === stamp) return destDir;
+    } catch {
+        // Not extracted yet
+    }
+
+    console.log(`[Server] Extracting model archive: ${zipPath}`);
+    await fs.rm(destDir, { recursive: true, force: true });
+    await extractZip(zipPath, destDir);
+    await fs.writeFile(stampFile, stamp);
+    return destDir;
+}
+
+// Fail fast with a clear message when an explicitly configured MODELS_DIR is unusable;
+// the default ./models is created when missing
+async function checkModelsDir() {
+    const dir = CONFIG.MODELS_DIR;
+    try {
+        const stat = await fs.stat(dir);
+        if (!stat.isDirectory()) throw new Error(`MODELS_DIR '${dir}' does not exist or is not a directory`);
+        await fs.access(dir, fsSync.constants.R_OK | fsSync.constants.X_OK);
+    } catch (err) {
+        if (err.code === 'ENOENT' && !process.env.MODELS_DIR) {
+            await fs.mkdir(dir, { recursive: true });
+            console.log(`[Server] Created models directory ${dir}`);
+            return;
+        }
+        if (err.code === 'ENOENT') throw new Error(`MODELS_DIR '${dir}' does not exist or is not a directory`);
+        if (err.code === 'EACCES' || err.code === 'EPERM') throw new Error(`MODELS_DIR '${dir}' is not readable (permission denied)`);
+        throw err;
+    }
+}
+
+// MODELS_MANIFEST: { "models": [{ "from", "to", "path", "domain"?, "aliases"?: ["from-to", ...] }] }.
+// Paths are relative to the manifest file. Returns the entries in manifest order with their
+// registry key and resolved directory; throws on a malformed manifest or a missing model path.
+async function readModelsManifest() {
+    const manifest = JSON.parse(await fs.readFile(CONFIG.MODELS_MANIFEST, 'utf8'));
+    if (!Array.isArray(manifest?.models)) throw new Error('expected a "models" array');
+    const baseDir = path.dirname(CONFIG.MODELS_MANIFEST);
+    const entries = [];
+    for (const [i, model] of manifest.models.entries()) {
+        const { from, to, domain = null, aliases = [] } = model || {};
+        if (typeof from !== 'string' || typeof to !== 'string' || typeof model.path !== 'string') {
+            throw new Error(`models[${i}] needs string from, to and path`);
+        }
+        if (!Array.isArray(aliases) || !aliases.every(alias => typeof alias === 'string')) {
+            throw new Error(`models[${i}].aliases must be an array of pair keys`);
+        }
+        const dir = path.resolve(baseDir, model.path);
+        if (!fsSync.existsSync(dir)) throw new Error(`models[${i}] (${from}-${to}) path does not exist: ${dir}`);
+        entries.push({ key: modelKey(from, to, domain), from, to, domain, dir, aliases: aliases.map(normalizePairName) });
+    }
+    return entries;
+}
+
+// Register exactly the manifest's models, in order; aliases resolve to their model's key
+async function loadModelsManifest() {
+    let entries;
+    try {
+        entries = await readModelsManifest();
+    } catch (err) {
+        throw new Error(`Invalid MODELS_MANIFEST ${CONFIG.MODELS_MANIFEST}: ${err.message}`);
+    }
+    for (const { key, from, to, domain, dir, aliases } of entries) {
+        availableModels.set(key, { dir, from, to, domain, buffers: null });
+        for (const alias of aliases) modelAliases.set(alias, key);
+    }
+    const keys = [...availableModels.keys(), ...Array.from(modelAliases, ([alias, key]) => `${alias} (alias of ${key})`)];
+    console.log(`[Server] Registered ${entries.length} models from ${CONFIG.MODELS_MANIFEST}: ${keys.join(', ')}`);
+}
+
 async function scanModelDirectories() {
     try {
         const entries = await fs.readdir(CONFIG.MODELS_DIR, { withFileTypes: true });
         let discovered = 0;
 
         for (const entry of entries) {
-            if (entry.isDirectory()) {
-                // Parse directory name (supports "enzh", "en-zh", "enja", "en-ja")
-                let from, to;
-                const name = entry.name;
-
-                if (name.includes('-')) {
-                    [from, to] = name.split('-');
-                } else if (name.length >= 4) {
-                    from = name.slice(0, 2);
-                    to = name.slice(2, 4);
-                } else {
-                    continue; // Skip invalid directory names
-                }
-
-                const dir = path.join(CONFIG.MODELS_DIR, entry.name);
-                const key = `${from}-${to}`;
-
-                // Just register the model directory, don't load yet
-                if (!availableModels.has(key)) {
-                    availableModels.set(key, { dir, from, to, buffers: null });
-                    discovered++;
+            const isZip = entry.isFile() && entry.name.toLowerCase().endsWith('.zip');
+            if (!entry.isDirectory() && !isZip) continue;
+
+            const name = isZip ? entry.name.slice(0, -4) : entry.name;
+            const parsed = parseModelDirName(name);
+            if (!parsed) continue; // Skip invalid directory names
+
+            const { from, to, domain } = parsed;
+            const key = modelKey(from, to, domain);
+            if (availableModels.has(key)) continue;
+
+            let dir = path.join(CONFIG.MODELS_DIR, entry.name);
+            if (isZip) {
+                try {
+                    dir = await extractModelZip(dir, name);
+                } catch (err) {
+                    console.error(`[Server] Failed to extract ${entry.name}: ${err.message}`);
+                    continue;
                 }
             }
+
+            // Just register the model directory, don't load yet
+            availableModels.set(key, { dir, from, to, domain, buffers: null });
+            discovered++;
         }
 
         console.log(`[Server] Discovered ${discovered} models: ${Array.from(availableModels.keys()).join(', ')}`);
@@ -939,35 +3443,214 @@ async function scanModelDirectories() {
     }
 }
 
-// Preload model buffers (without WASM instantiation) for faster first translation
+// ============== Model Comparison ==============
+
+// Resolve a model reference for /compare: a registered key ("en-zh", "en-zh:v2") or a
+// model directory name ("enzh-v2", "en-zh-v2")
+function resolveModelRef(ref) {
+    if (typeof ref !== 'string' || !ref) return null;
+    if (availableModels.has(canonicalModelKey(ref))) return canonicalModelKey(ref);
+    const parsed = parseModelDirName(ref);
+    return parsed ? modelKey(parsed.from, parsed.to, parsed.domain) : null;
+}
+
+// Normalized edit-distance similarity in [0, 1] over code points (1 = identical)
+function textSimilarity(a, b) {
+    const x = [...a];
+    const y = [...b];
+    if (x.length === 0 && y.length === 0) return 1;
+    let prev = Array.from({ length: y.length + 1 }, (_, j) => j);
+    for (let i = 1; i <= x.length; i++) {
+        const curr = [i];
+        for (let j = 1; j <= y.length; j++) {
+            curr[j] = Math.min(prev[j] + 1, curr[j - 1] + 1, prev[j - 1] + (x[i - 1] === y[j - 1] ? 0 : 1));
+        }
+        prev = curr;
+    }
+    return 1 - prev[y.length] / Math.max(x.length, y.length);
+}
+
+// Translate the same text with two models (e.g. enzh-v1 vs enzh-v2) for upgrade evaluation.
+// Auth-gated since it doubles the translation work and swaps the active model.
+app.post('/compare', async (req, res) => {
+    const { text, models } = req.body;
+    if (!text || typeof text !== 'string') return badRequest(res, 'Missing text');
+    if (!Array.isArray(models) || models.length !== 2) return badRequest(res, 'models must list exactly two models');
+    const keys = models.map(resolveModelRef);
+    const unknown = models.filter((ref, i) => !keys[i] || !availableModels.has(keys[i]));
+    if (unknown.length > 0) {
+        return sendError(res, new ApiError(400, 'MODEL_NOT_AVAILABLE', `Unknown model: ${unknown.join(', ')}`, {
+            available_models: Array.from(availableModels.keys()),
+        }));
+    }
+
+    try {
+        // Both pairs are charged up front, so a request refused for one doesn't use up the other
+        checkPairRateLimits(keys.map(key => [availableModels.get(key).from, availableModels.get(key).to]));
+        const results = [];
+        for (const key of keys) {
+            const model = await loadModel(key);
+            const start = process.hrtime.bigint();
+            const translated = translateText(model, text);
+            results.push({
+                model: key,
+                dir: modelDirName(model),
+                text: translated,
+                latency_ms: Math.round(Number(process.hrtime.bigint() - start) / 1e6),
+            });
+        }
+        const similarity = Math.round(textSimilarity(results[0].text, results[1].text) * 1000) / 1000;
+        res.json({ source: text, results, similarity, identical: results[0].text === results[1].text });
+    } catch (err) {
+        sendError(res, err);
+    }
+});
+
+// ============== Glossary Admin ==============
+
+// Inspect and edit the deployment glossary at runtime. Changes apply to the next translation;
+// add ?persist=1 to also write the glossary back to GLOSSARY_FILE.
+app.get('/glossary', (req, res) => {
+    res.json({ glossary: serverGlossary, count: Object.keys(serverGlossary).length });
+});
+
+app.put('/glossary/:term', async (req, res) => {
+    const { term } = req.params;
+    const { target } = req.body || {};
+    if (typeof target !== 'string') return badRequest(res, 'Missing target');
+    if (req.query.persist === '1' && !CONFIG.GLOSSARY_FILE) return badRequest(res, 'persist requires GLOSSARY_FILE');
+
+    serverGlossary = { ...serverGlossary, [term]: target };
+    try {
+        if (req.query.persist === '1') await saveGlossaryFile();
+        res.json({ success: true, term, target });
+    } catch (err) {
+        sendError(res, err);
+    }
+});
+
+app.delete('/glossary/:term', async (req, res) => {
+    const { term } = req.params;
+    if (req.query.persist === '1' && !CONFIG.GLOSSARY_FILE) return badRequest(res, 'persist requires GLOSSARY_FILE');
+    if (!Object.hasOwn(serverGlossary, term)) {
+        return sendError(res, new ApiError(404, 'NOT_FOUND', `Glossary term not found: ${term}`));
+    }
+
+    const { [term]: _removed, ...rest } = serverGlossary;
+    serverGlossary = rest;
+    try {
+        if (req.query.persist === '1') await saveGlossaryFile();
+        res.json({ success: true, term });
+    } catch (err) {
+        sendError(res, err);
+    }
+});
+
+// Preload model buffers (without WASM instantiation) for faster first translation.
+// Reads up to MODEL_LOAD_CONCURRENCY models at a time; availableModels keeps scan order.
+// With MODEL_CACHE_BUDGET set, only the first MODEL_CACHE_BUDGET models are preloaded.
 async function preloadModelBuffers() {
-    for (const [key, modelInfo] of availableModels) {
-        try {
-            if (!modelInfo.buffers) {
+    const pending = Array.from(availableModels.entries())
+        .filter(([, info]) => !info.buffers)
+        .slice(0, CONFIG.MODEL_CACHE_BUDGET || undefined);
+    let next = 0;
+
+    async function worker() {
+        while (next < pending.length) {
+            const [key, modelInfo] = pending[next++];
+            try {
                 modelInfo.buffers = await loadModelFiles(modelInfo.dir);
+                touchModelBuffers(key);
                 console.log(`[Server] Preloaded buffers for ${key}`);
+            } catch (err) {
+                modelInfo.loadError = err.message;
+                console.error(`[Server] Failed to preload ${key}: ${err.message}`);
             }
+        }
+    }
+
+    const workers = Math.min(CONFIG.MODEL_LOAD_CONCURRENCY, pending.length);
+    await Promise.all(Array.from({ length: workers }, worker));
+}
+
+// Canned sentences for STARTUP_SELFTEST, by source language (English for anything else)
+const SELFTEST_SENTENCES = {
+    en: 'The weather is nice today.',
+    zh: '今天天气很好。',
+    ja: '今日はいい天気です。',
+    ko: '오늘은 날씨가 좋다.',
+    de: 'Heute ist das Wetter schön.',
+    fr: "Il fait beau aujourd'hui.",
+    es: 'Hoy hace buen tiempo.',
+    ru: 'Сегодня хорошая погода.',
+};
+
+// STARTUP_SELFTEST=1: translate a canned sentence with every registered pair before serving.
+// Empty output or output identical to the input marks the pair unhealthy (loadError, shown by
+// /health); a failing REQUIRED_PAIRS entry aborts startup.
+async function runStartupSelfTest() {
+    const failed = [];
+    for (const [key, modelInfo] of availableModels) {
+        if (modelInfo.loadError) continue;
+        const sentence = SELFTEST_SENTENCES[normalizeLanguageCode(modelInfo.from)] || SELFTEST_SENTENCES.en;
+        let problem;
+        try {
+            // The raw engine output: doTranslate would pass the source through in place of an empty result
+            const output = runBergamot(await loadModel(key), sentence);
+            if (!output.trim()) problem = 'empty output';
+            else if (output.trim() === sentence) problem = 'output identical to input';
         } catch (err) {
-            console.error(`[Server] Failed to preload ${key}: ${err.message}`);
+            problem = err.message;
+        }
+        if (problem) {
+            modelInfo.loadError = `Startup self-test failed: ${problem}`;
+            failed.push(key);
+            console.error(`[Server] Self-test failed for ${key}: ${problem}`);
+        } else {
+            console.log(`[Server] Self-test passed for ${key}`);
         }
     }
+    const required = failed.filter(key => CONFIG.REQUIRED_PAIRS.some(pair => canonicalModelKey(pair) === key));
+    if (required.length > 0) {
+        throw new Error(`Startup self-test failed for required pair(s): ${required.join(', ')}`);
+    }
 }
 
 // ============== Start Server ==============
 
 async function start() {
     try {
-        // Scan for available models (don't load yet)
-        await scanModelDirectories();
+        bergamotConfigTemplate = loadBergamotConfigTemplate();
+        serverGlossary = loadGlossaryFile();
+        if (CONFIG.API_KEY_FILE || CONFIG.API_KEYS_FILE) apiKeys = readApiKeyFiles();
+        if (CONFIG.SIGN_RESPONSES && !CONFIG.RESPONSE_HMAC_KEY) throw new Error('SIGN_RESPONSES requires RESPONSE_HMAC_KEY');
+
+        // Register models from MODELS_MANIFEST, or scan MODELS_DIR for them (don't load yet)
+        if (CONFIG.MODELS_MANIFEST) {
+            await loadModelsManifest();
+        } else {
+            await checkModelsDir();
+            await scanModelDirectories();
+        }
 
         // Preload model buffers for faster first translation
         await preloadModelBuffers();
+        if (CONFIG.STARTUP_SELFTEST) await runStartupSelfTest();
 
         // Start Express server
-        app.listen(CONFIG.PORT, CONFIG.IP, () => {
+        const server = app.listen(CONFIG.PORT, CONFIG.IP, () => {
             console.log(`[Server] LinguaSpark listening on http://${CONFIG.IP}:${CONFIG.PORT}`);
             console.log(`[Server] Models directory: ${CONFIG.MODELS_DIR}`);
-            if (CONFIG.API_KEY) console.log(`[Server] API key protection enabled`);
+            if (apiKeys.length > 0) console.log(`[Server] API key protection enabled (${apiKeys.length} key${apiKeys.length === 1 ? '' : 's'})`);
+        });
+        server.on('upgrade', handleStatusUpgrade);
+        server.on('error', (err) => {
+            if (err.code === 'EADDRINUSE') {
+                console.error(`[Server] Port ${CONFIG.PORT} on ${CONFIG.IP} is already in use (is another instance still running?)`);
+            } else {
+                console.error('[Server] Failed to start:', err);
+            }
+            process.exit(1);
         });
 
     } catch (err) {
@@ -976,6 +3659,62 @@ async function start() {
     }
 }
 
+// `node server.js --check-config`: validate settings, the Bergamot files and the model directory
+// names without preloading models or binding the port. Returns true when nothing is wrong.
+async function checkConfig() {
+    const problems = [...configWarnings];
+    const languageCode = /^[a-z]{2,3}$/i;
+
+    for (const [name, file] of [['WASM_PATH', CONFIG.WASM_PATH], ['JS_PATH', CONFIG.JS_PATH], ['BERGAMOT_CONFIG_FILE', CONFIG.BERGAMOT_CONFIG_FILE]]) {
+        if (file && !fsSync.existsSync(file)) problems.push(`${name} does not exist: ${file}`);
+    }
+    try {
+        readApiKeyFiles();
+    } catch (err) {
+        problems.push(`Invalid API key file: ${err.message}`);
+    }
+    if (CONFIG.SIGN_RESPONSES && !CONFIG.RESPONSE_HMAC_KEY) problems.push('SIGN_RESPONSES requires RESPONSE_HMAC_KEY');
+
+    const pairs = new Set();
+    if (CONFIG.MODELS_MANIFEST) {
+        try {
+            for (const { key, dir, aliases } of await readModelsManifest()) {
+                [key, ...aliases].forEach(k => pairs.add(k));
+                console.log(`[Check] ${dir} -> ${[key, ...aliases].join(', ')}`);
+            }
+        } catch (err) {
+            problems.push(`Invalid MODELS_MANIFEST ${CONFIG.MODELS_MANIFEST}: ${err.message}`);
+        }
+    } else {
+        try {
+            const entries = await fs.readdir(CONFIG.MODELS_DIR, { withFileTypes: true });
+            for (const entry of entries) {
+                const isZip = entry.isFile() && entry.name.toLowerCase().endsWith('.zip');
+                if (!entry.isDirectory() && !isZip) continue;
+                const name = isZip ? entry.name.slice(0, -4) : entry.name;
+                const parsed = parseModelDirName(name);
+                if (!parsed || !languageCode.test(parsed.from) || !languageCode.test(parsed.to)) {
+                    problems.push(`Invalid model directory name '${entry.name}' (expected e.g. enzh, en-zh or enzh-medical)`);
+                    continue;
+                }
+                const key = modelKey(parsed.from, parsed.to, parsed.domain);
+                pairs.add(key);
+                console.log(`[Check] ${entry.name} -> ${key}`);
+            }
+        } catch (err) {
+            problems.push(`Cannot read MODELS_DIR ${CONFIG.MODELS_DIR}: ${err.message}`);
+        }
+    }
+
+    for (const key of CONFIG.REQUIRED_PAIRS) {
+        if (!pairs.has(key)) problems.push(`REQUIRED_PAIRS entry ${key} has no model directory`);
+    }
+
+    for (const problem of problems) console.error(`[Check] ${problem}`);
+    console.log(problems.length === 0 ? '[Check] Configuration OK' : `[Check] ${problems.length} problem(s) found`);
+    return problems.length === 0;
+}
+
 // Shutdown
 process.on('SIGTERM', () => {
     console.log('[Server] Shutting down...');
@@ -993,4 +3732,8 @@ process.on('SIGINT', () => {
     process.exit(0);
 });
 
-start();
\ No newline at end of file
+if (process.argv.includes('--check-config')) {
+    process.exit(await checkConfig() ? 0 : 1);
+} else {
+    start();
+}
\ No newline at end of file
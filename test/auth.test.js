@@ -0,0 +1,109 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ env: { API_KEY: 'secret' } }); });
+after(() => server.stop());
+
+const hello = { text: 'Hello world', from: 'en', to: 'zh' };
+
+test('API routes require the key', async () => {
+    const res = await server.post('/translate', hello);
+    assert.equal(res.status, 401);
+    assert.equal((await res.json()).code, 'UNAUTHORIZED');
+});
+
+test('Bearer header, ?token= and a wrong key', async () => {
+    assert.equal((await server.post('/translate', hello, { Authorization: 'Bearer secret' })).status, 200);
+    assert.equal((await server.post('/translate?token=secret', hello)).status, 200);
+    assert.equal((await server.post('/translate', hello, { Authorization: 'Bearer wrong' })).status, 401);
+});
+
+test('PUBLIC_PATHS are reachable without the key', async () => {
+    for (const route of ['/health', '/models', '/translate/options']) {
+        assert.equal((await server.fetch(route)).status, 200, route);
+    }
+});
+
+test('routes outside PUBLIC_PATHS stay protected', async () => {
+    assert.equal((await server.fetch('/cache/export')).status, 401);
+    assert.equal((await server.fetch('/monitor/export')).status, 401);
+    assert.equal((await server.fetch('/debug/requests')).status, 401);
+});
+
+test('monitor and MTranServer routes need the key by default', async () => {
+    assert.equal((await server.fetch('/monitor/logs')).status, 401);
+    assert.equal((await server.fetch('/monitor/clear', { method: 'POST' })).status, 401);
+    assert.equal((await server.post('/translate_mtranserver', hello)).status, 401);
+    assert.equal((await server.post('/translate_mtranserver', hello, { Authorization: 'Bearer secret' })).status, 200);
+});
+
+test('PUBLIC_PATHS opts legacy clients back in', async () => {
+    const legacy = await startServer({ env: { API_KEY: 'secret', PUBLIC_PATHS: '/health,/monitor/logs,/translate_mtranserver*' } });
+    try {
+        assert.equal((await legacy.fetch('/monitor/logs')).status, 200);
+        assert.equal((await legacy.post('/translate_mtranserver', hello)).status, 200);
+        assert.equal((await legacy.fetch('/models')).status, 401);
+    } finally {
+        await legacy.stop();
+    }
+});
+
+test('API_KEY_HEADER accepts the key in a custom header as well', async () => {
+    const custom = await startServer({ env: { API_KEY: 'secret', API_KEY_HEADER: 'X-API-Key' } });
+    try {
+        assert.equal((await custom.post('/translate', hello, { 'X-API-Key': 'secret' })).status, 200);
+        assert.equal((await custom.post('/translate', hello, { 'X-API-Key': 'wrong' })).status, 401);
+        assert.equal((await custom.post('/translate', hello, { Authorization: 'Bearer secret' })).status, 200);
+        // Without API_KEY_HEADER the custom header is ignored
+        assert.equal((await server.post('/translate', hello, { 'X-API-Key': 'secret' })).status, 401);
+    } finally {
+        await custom.stop();
+    }
+});
+
+test('API_KEY_FILE replaces the inline API_KEY', async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'linguaspark-keys-'));
+    const keyFile = path.join(dir, 'api-key');
+    await fs.writeFile(keyFile, 'from-file\n');
+    const fromFile = await startServer({ env: { API_KEY: 'secret', API_KEY_FILE: keyFile } });
+    try {
+        assert.equal((await fromFile.post('/translate', hello, { Authorization: 'Bearer from-file' })).status, 200);
+        assert.equal((await fromFile.post('/translate', hello, { Authorization: 'Bearer secret' })).status, 401);
+    } finally {
+        await fromFile.stop();
+        await fs.rm(dir, { recursive: true, force: true });
+    }
+});
+
+test('API_KEYS_FILE accepts every listed key', async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'linguaspark-keys-'));
+    const keysFile = path.join(dir, 'api-keys');
+    await fs.writeFile(keysFile, '# rotated 2026-10\nnew-key\n\n  old-key  \r\n');
+    const fromFile = await startServer({ env: { API_KEYS_FILE: keysFile } });
+    try {
+        for (const key of ['new-key', 'old-key']) {
+            assert.equal((await fromFile.post('/translate', hello, { Authorization: `Bearer ${key}` })).status, 200, key);
+        }
+        assert.equal((await fromFile.post('/translate', hello)).status, 401);
+        assert.equal((await fromFile.post('/translate', hello, { Authorization: 'Bearer # rotated 2026-10' })).status, 401);
+    } finally {
+        await fromFile.stop();
+        await fs.rm(dir, { recursive: true, force: true });
+    }
+});
+
+test('an empty API_KEY_FILE fails startup instead of turning auth off', async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'linguaspark-keys-'));
+    const keyFile = path.join(dir, 'api-key');
+    await fs.writeFile(keyFile, '\n');
+    try {
+        await assert.rejects(startServer({ env: { API_KEY_FILE: keyFile } }), /API_KEY_FILE .* is empty/);
+    } finally {
+        await fs.rm(dir, { recursive: true, force: true });
+    }
+});
@@ -0,0 +1,40 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ env: { MAX_BATCH_SIZE: '3', MAX_BATCH_CHARS: '20' } }); });
+after(() => server.stop());
+
+const batch = texts => server.post('/translate_mtranserver/batch', { texts, from: 'en', to: 'zh' });
+
+test('a batch within the limits is translated', async () => {
+    const res = await batch(['One', 'Two', 'Three']);
+    assert.deepEqual(await res.json(), { results: ['[zh] One', '[zh] Two', '[zh] Three'] });
+});
+
+test('too many segments is a 400 naming the limits and the received sizes', async () => {
+    const res = await batch(['a', 'b', 'c', 'd']);
+    assert.equal(res.status, 400);
+    assert.deepEqual(await res.json(), {
+        error: 'Batch too large: received 4 segments / 4 chars (limits: 3 segments / 20 chars)',
+        code: 'BATCH_TOO_LARGE',
+        max_segments: 3,
+        max_chars: 20,
+        segments: 4,
+        chars: 4,
+    });
+});
+
+test('too many characters is a 400', async () => {
+    const res = await batch(['Hello there', 'General Kenobi']);
+    assert.equal(res.status, 400);
+    assert.match((await res.json()).error, /received 2 segments \/ 25 chars/);
+});
+
+test('the other batch endpoints enforce the same limits', async () => {
+    const ndjson = await server.post('/translate/ndjson', { texts: ['a', 'b', 'c', 'd'], from: 'en', to: 'zh' });
+    assert.equal(ndjson.status, 400);
+    const detect = await server.post('/detect', { text: ['a', 'b', 'c', 'd'] });
+    assert.equal(detect.status, 400);
+});
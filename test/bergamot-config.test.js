@@ -0,0 +1,79 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+import { startServer } from './helpers.js';
+
+// The stub answers SHOWCONFIG with the config its TranslationModel was created with
+async function modelConfig(server, from, to) {
+    const res = await server.post('/translate', { text: 'SHOWCONFIG', from, to });
+    assert.equal(res.status, 200);
+    return (await res.json()).text;
+}
+
+let configDir;
+before(async () => { configDir = await fs.mkdtemp(path.join(os.tmpdir(), 'linguaspark-config-')); });
+after(() => fs.rm(configDir, { recursive: true, force: true }));
+
+test('BERGAMOT_CONFIG_FILE replaces the built-in config; a pair file still overrides it', async () => {
+    const template = path.join(configDir, 'bergamot.yml');
+    await fs.writeFile(template, 'beam-size: 4\ncpu-threads: 8\nmini-batch-words: 2048\n');
+    const server = await startServer({
+        env: { BERGAMOT_CONFIG_FILE: template },
+        prepare: modelsDir => fs.writeFile(path.join(modelsDir, 'zh-en', 'bergamot-config.yml'), 'beam-size: 2\n'),
+    });
+    try {
+        assert.match(server.output(), /Using Bergamot config template from .*bergamot\.yml/);
+        assert.equal(await modelConfig(server, 'en', 'zh'), 'beam-size: 4\ncpu-threads: 8\nmini-batch-words: 2048');
+        assert.equal(await modelConfig(server, 'zh', 'en'), 'beam-size: 2');
+    } finally {
+        await server.stop();
+    }
+});
+
+test('without BERGAMOT_CONFIG_FILE the built-in config is used', async () => {
+    const server = await startServer();
+    try {
+        const config = await modelConfig(server, 'en', 'zh');
+        assert.match(config, /^beam-size: 1$/m);
+        assert.match(config, /^mini-batch-words: 1024$/m);
+    } finally {
+        await server.stop();
+    }
+});
+
+test('an unreadable BERGAMOT_CONFIG_FILE warns and keeps the built-in config', async () => {
+    const server = await startServer({ env: { BERGAMOT_CONFIG_FILE: path.join(configDir, 'missing.yml') } });
+    try {
+        assert.match(server.output(), /Cannot read BERGAMOT_CONFIG_FILE .*using built-in config/);
+        assert.match(await modelConfig(server, 'en', 'zh'), /^mini-batch-words: 1024$/m);
+    } finally {
+        await server.stop();
+    }
+});
+
+test('PAIR_OPTIONS set decoding options for one pair, over its config', async () => {
+    const server = await startServer({
+        pairs: ['en-zh', 'en-zh-medical', 'zh-en', 'en-ja'],
+        env: { PAIR_OPTIONS: 'enzh:beam=4,normalize=0.6;zhen:beam=2' },
+        prepare: modelsDir => fs.writeFile(path.join(modelsDir, 'zh-en', 'bergamot-config.yml'), 'beam-size: 6\ncpu-threads: 2\n'),
+    });
+    try {
+        const enzh = await modelConfig(server, 'en', 'zh');
+        assert.match(enzh, /^beam-size: 4$/m);
+        assert.match(enzh, /^normalize: 0.6$/m);
+        assert.match(enzh, /^mini-batch-words: 1024$/m);
+        assert.match(server.output(), /PAIR_OPTIONS for en-zh: beam-size=4, normalize=0.6/);
+        // Every domain variant of the pair gets them
+        const medical = await server.post('/translate', { text: 'SHOWCONFIG', from: 'en', to: 'zh', domain: 'medical' });
+        assert.match((await medical.json()).text, /^beam-size: 4$/m);
+        assert.match(server.output(), /Creating WASM instance for model: en-zh:medical/);
+        // The pair default wins over the pair's config file; its other options are kept
+        assert.equal(await modelConfig(server, 'zh', 'en'), 'beam-size: 2\ncpu-threads: 2');
+        // Pairs without PAIR_OPTIONS keep the global default
+        assert.match(await modelConfig(server, 'en', 'ja'), /^beam-size: 1$/m);
+    } finally {
+        await server.stop();
+    }
+});
@@ -0,0 +1,25 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ pairs: ['en-he', 'en-zh'] }); });
+after(() => server.stop());
+
+const text = 'See https://example.com/docs for 42 tips';
+const RLI = '\u2067', LRI = '\u2066', PDI = '\u2069';
+
+test('bidi_marks isolates Hebrew output and each embedded LTR entity', async () => {
+    const res = await server.post('/translate', { text, from: 'en', to: 'he', bidi_marks: true });
+    assert.equal((await res.json()).text, `${RLI}[he] See ${LRI}https://example.com/docs${PDI} for ${LRI}42${PDI} tips${PDI}`);
+});
+
+test('without bidi_marks the output has no bidi controls', async () => {
+    const res = await server.post('/translate', { text, from: 'en', to: 'he' });
+    assert.equal((await res.json()).text, '[he] See https://example.com/docs for 42 tips');
+});
+
+test('bidi_marks leaves left-to-right targets alone', async () => {
+    const res = await server.post('/translate', { text, from: 'en', to: 'zh', bidi_marks: true });
+    assert.equal((await res.json()).text, '[zh] See https://example.com/docs for 42 tips');
+});
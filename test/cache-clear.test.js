@@ -0,0 +1,33 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+const count = (pattern) => server.output().split('\n').filter(line => pattern.test(line)).length;
+
+test('clearing one pair reloads its model files on the next request', async () => {
+    assert.equal((await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' })).status, 200);
+    assert.equal(count(/Loading model files: en-zh/), 0);
+
+    const res = await server.post('/cache/clear', { from: 'en', to: 'zh' });
+    assert.deepEqual(await res.json(), { success: true, cleared: 1 });
+
+    assert.equal((await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' })).status, 200);
+    assert.equal(count(/Loading model files: en-zh/), 1);
+    assert.equal(count(/Creating WASM instance for model: en-zh/), 2);
+    // zh-en kept its preloaded buffers
+    assert.equal((await server.post('/translate', { text: '你好', from: 'zh', to: 'en' })).status, 200);
+    assert.equal(count(/Loading model files: zh-en/), 0);
+});
+
+test('clearing without a filter drops every cached model', async () => {
+    const res = await server.post('/cache/clear', {});
+    assert.deepEqual(await res.json(), { success: true, cleared: 2 });
+});
+
+test('from without to is a 400', async () => {
+    assert.equal((await server.post('/cache/clear', { from: 'en' })).status, 400);
+});
@@ -0,0 +1,29 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => {
+    server = await startServer({ pairs: ['en-zh', 'en-ja'], env: { PAIR_RATE_LIMITS: 'enzh:1,enja:2' } });
+});
+after(() => server.stop());
+
+const compare = () => server.post('/compare', { text: 'Hello', models: ['en-ja', 'en-zh'] });
+
+test('/compare translates with both models', async () => {
+    const res = await compare();
+    assert.equal(res.status, 200);
+    const body = await res.json();
+    assert.deepEqual(body.results.map(r => [r.model, r.text]), [['en-ja', '[ja] Hello'], ['en-zh', '[zh] Hello']]);
+    assert.equal(body.identical, false);
+});
+
+test('/compare charges neither pair when one is over its limit', async () => {
+    // en-zh used its one request above; en-ja has one of two left
+    const limited = await compare();
+    assert.equal(limited.status, 429);
+    assert.match((await limited.json()).error, /Rate limit exceeded for en-zh/);
+
+    assert.equal((await server.post('/translate', { text: 'Hello', from: 'en', to: 'ja' })).status, 200);
+    assert.equal((await server.post('/translate', { text: 'Hello', from: 'en', to: 'ja' })).status, 429);
+});
@@ -0,0 +1,33 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { spawnSync } from 'node:child_process';
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import { ROOT, STUB_GLUE, createModelsDir } from './helpers.js';
+
+let modelsDir;
+before(async () => { modelsDir = await createModelsDir(['en-zh']); });
+after(() => fs.rm(modelsDir, { recursive: true, force: true }));
+
+function checkConfig(env) {
+    return spawnSync(process.execPath, [path.join(ROOT, 'server.js'), '--check-config'], {
+        cwd: ROOT,
+        env: { PATH: process.env.PATH, MODELS_DIR: modelsDir, WASM_PATH: STUB_GLUE, JS_PATH: STUB_GLUE, ...env },
+        encoding: 'utf8',
+    });
+}
+
+test('a valid configuration passes', () => {
+    const result = checkConfig({ PAIR_RATE_LIMITS: 'enzh:100', PAIR_OPTIONS: 'enzh:beam=4' });
+    assert.equal(result.status, 0, result.stderr);
+    assert.match(result.stdout, /Configuration OK/);
+});
+
+test('malformed PAIR_RATE_LIMITS and PAIR_OPTIONS entries are reported', () => {
+    const result = checkConfig({ PAIR_RATE_LIMITS: 'enzh:100,jpen:many', PAIR_OPTIONS: 'enzh:beam=4,bad option;jpen' });
+    assert.equal(result.status, 1);
+    assert.match(result.stderr, /\[Check\] Ignoring invalid PAIR_RATE_LIMITS entry: jpen:many/);
+    assert.match(result.stderr, /\[Check\] Ignoring invalid PAIR_OPTIONS option for enzh: bad option/);
+    assert.match(result.stderr, /\[Check\] Ignoring invalid PAIR_OPTIONS entry: jpen/);
+    assert.match(result.stdout, /3 problem\(s\) found/);
+});
@@ -0,0 +1,37 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+// The stub reads "Close" as the verb "Close(v)" when its input mentions a button
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+async function translate(body, target = server) {
+    const res = await target.post('/translate', { from: 'en', to: 'zh', ...body });
+    assert.equal(res.status, 200);
+    return (await res.json()).text;
+}
+
+test('the context changes the translation and is not in the result', async () => {
+    assert.equal(await translate({ text: 'Close' }), '[zh] Close');
+    assert.equal(await translate({ text: 'Close', context: 'dialog button' }), 'Close(v)');
+});
+
+test('colons in the text or the context are kept', async () => {
+    assert.equal(await translate({ text: 'Note: press Close', context: 'toolbar: button' }), 'Note: press Close(v)');
+});
+
+test('the boundary does not clash with protected placeholders', async () => {
+    assert.equal(await translate({ text: 'Close {file} now', context: 'dialog button', protect_placeholders: true }), 'Close(v) {file} now');
+});
+
+test('when the boundary is lost the text is translated alone', async () => {
+    // The stub drops <<n>> placeholders
+    const dropping = await startServer({ env: { PLACEHOLDER_FORMAT: '<<{n}>>' } });
+    try {
+        assert.equal(await translate({ text: 'Close', context: 'dialog button' }, dropping), '[zh] Close');
+    } finally {
+        await dropping.stop();
+    }
+});
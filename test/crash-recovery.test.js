@@ -0,0 +1,43 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+const document = 'Hello one\n\nCRASH two\n\nHello three\n\nHello four';
+
+test('document, continue: segments after a crash are translated by a fresh instance', async () => {
+    const res = await server.post('/translate/document', { text: document, from: 'en', to: 'zh', on_error: 'continue' });
+    assert.equal(res.status, 200);
+    const body = await res.json();
+    assert.equal(body.text, '[zh] Hello one\n\n[untranslated] CRASH two\n\n[zh] Hello three\n\n[zh] Hello four');
+    assert.equal(body.errors.length, 1);
+    assert.equal(body.errors[0].index, 1);
+    assert.match(body.errors[0].error, /translation engine crashed/);
+});
+
+test('document, abort: a crash is a 502 TRANSLATOR_UNAVAILABLE', async () => {
+    const res = await server.post('/translate/document', { text: document, from: 'en', to: 'zh', on_error: 'abort' });
+    assert.equal(res.status, 502);
+    assert.equal((await res.json()).code, 'TRANSLATOR_UNAVAILABLE');
+    assert.equal(res.headers.get('retry-after'), '1');
+});
+
+test('NDJSON: lines after a crash are translated', async () => {
+    const res = await server.post('/translate/ndjson', { texts: ['Hello one', 'CRASH two', 'Hello three'], from: 'en', to: 'zh', on_error: 'continue' });
+    const lines = (await res.text()).trim().split('\n').map(line => JSON.parse(line));
+    assert.deepEqual(lines[0], { index: 0, text: '[zh] Hello one' });
+    assert.equal(lines[1].index, 1);
+    assert.match(lines[1].error, /translation engine crashed/);
+    assert.deepEqual(lines[2], { index: 2, text: '[zh] Hello three' });
+    assert.deepEqual(lines[3], { done: true, count: 3, errors: 1, from: 'en', to: 'zh' });
+});
+
+test('the next request gets a working instance and crashes are counted', async () => {
+    const res = await server.post('/translate', { text: 'Hello world', from: 'en', to: 'zh' });
+    assert.equal((await res.json()).text, '[zh] Hello world');
+    const stats = await (await server.fetch('/monitor/stats')).json();
+    assert.ok(stats.engineCrashes >= 5, `engineCrashes ${stats.engineCrashes}`);
+});
@@ -0,0 +1,63 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+// The stub spends 100 ms on each SLOW segment, so six of them cannot fit in a 250 ms budget
+const SEGMENTS = ['SLOW one.', 'SLOW two.', 'SLOW three.', 'SLOW four.', 'SLOW five.', 'SLOW six.'];
+const DEADLINE_MS = 250;
+
+let server;
+before(async () => {
+    server = await startServer();
+    // Create the WASM instance up front so the budget is spent on segments, not on the model load
+    await server.post('/translate', { text: 'warm up', from: 'en', to: 'zh' });
+});
+after(() => server.stop());
+
+// Segments are translated in order until the budget runs out; the rest are listed as incomplete
+function assertPartial(sources, targets, incomplete) {
+    assert.ok(incomplete.length > 0, 'expected the deadline to cut the batch short');
+    assert.ok(incomplete.length < sources.length, 'expected some segments to be translated');
+    const done = sources.length - incomplete.length;
+    assert.deepEqual(incomplete, sources.slice(done).map((_, i) => done + i));
+    sources.forEach((source, i) => {
+        assert.equal(targets[i], i < done ? `[zh] ${source}` : source);
+    });
+}
+
+test('/translate/document returns what it translated within deadline_ms', async () => {
+    const res = await server.post('/translate/document', {
+        text: SEGMENTS.join('\n\n'), from: 'en', to: 'zh', segmented: true, deadline_ms: DEADLINE_MS,
+    });
+    assert.equal(res.status, 200);
+    const body = await res.json();
+    assertPartial(SEGMENTS, body.segments.map(s => s.target), body.incomplete);
+});
+
+test('/translate_mtranserver/batch returns what it translated within deadline_ms', async () => {
+    const res = await server.post('/translate_mtranserver/batch', {
+        texts: SEGMENTS, from: 'en', to: 'zh', deadline_ms: DEADLINE_MS,
+    });
+    assert.equal(res.status, 200);
+    const body = await res.json();
+    assertPartial(SEGMENTS, body.results, body.incomplete);
+});
+
+test('a generous deadline_ms leaves nothing incomplete', async () => {
+    const res = await server.post('/translate_mtranserver/batch', {
+        texts: SEGMENTS.slice(0, 2), from: 'en', to: 'zh', deadline_ms: 60000,
+    });
+    const body = await res.json();
+    assert.deepEqual(body.results, ['[zh] SLOW one.', '[zh] SLOW two.']);
+    assert.equal(body.incomplete, undefined);
+});
+
+test('deadline_ms must be a positive number', async () => {
+    for (const deadline_ms of [0, -5, '500']) {
+        const res = await server.post('/translate/document', { text: 'Hello', from: 'en', to: 'zh', deadline_ms });
+        assert.equal(res.status, 400);
+        assert.match((await res.json()).error, /deadline_ms must be a positive number/);
+        const batch = await server.post('/translate_mtranserver/batch', { texts: ['Hello'], from: 'en', to: 'zh', deadline_ms });
+        assert.equal(batch.status, 400);
+    }
+});
@@ -0,0 +1,26 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+// Directory names as found on disk: model_used reports these, not the normalized pair keys
+before(async () => { server = await startServer({ pairs: ['fr-en', 'enzh', 'enzh-hq'] }); });
+after(() => server.stop());
+
+test('?debug=model lists the directory of a direct translation', async () => {
+    const res = await server.post('/translate?debug=model', { text: 'Hello world', from: 'en', to: 'zh' });
+    assert.deepEqual(await res.json(), { text: '[zh] Hello world', from: 'en', to: 'zh', model_used: ['enzh'] });
+
+    const hq = await server.post('/translate?debug=model', { text: 'Hello world', from: 'en', to: 'zh', quality: 'high' });
+    assert.deepEqual((await hq.json()).model_used, ['enzh-hq']);
+});
+
+test('?debug=model lists both legs of a pivot', async () => {
+    const res = await server.post('/translate?debug=model', { text: 'Bonjour le monde', from: 'fr', to: 'zh' });
+    assert.deepEqual((await res.json()).model_used, ['fr-en', 'enzh']);
+});
+
+test('model_used is only reported when asked for', async () => {
+    const res = await server.post('/translate', { text: 'Hello world', from: 'en', to: 'zh' });
+    assert.equal((await res.json()).model_used, undefined);
+});
@@ -0,0 +1,29 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ pairs: ['en-zh', 'zh-en', 'jp-en'] }); });
+after(() => server.stop());
+
+// Kanji only: as readily Chinese as Japanese
+const kanji = '東京大学図書館';
+
+test('an uncertain auto-detection lists the alternatives with scores summing to 1', async () => {
+    const res = await server.post('/translate', { text: kanji, to: 'en', detect_alternatives: true });
+    assert.equal(res.status, 200);
+    const body = await res.json();
+    assert.ok(body.from_alternatives.length >= 2, JSON.stringify(body));
+    assert.ok(body.from_alternatives.some(a => a.lang === 'zh'));
+    assert.equal(body.from_alternatives[0].lang, body.from);
+    for (const { conf } of body.from_alternatives) assert.ok(conf > 0 && conf <= 1);
+    const total = body.from_alternatives.reduce((sum, a) => sum + a.conf, 0);
+    assert.ok(Math.abs(total - 1) <= 0.02, `sum ${total}`);
+});
+
+test('no alternatives for an explicit from or without the flag', async () => {
+    const explicit = await server.post('/translate', { text: kanji, from: 'zh', to: 'en', detect_alternatives: true });
+    assert.equal((await explicit.json()).from_alternatives, undefined);
+    const unflagged = await server.post('/translate', { text: kanji, to: 'en' });
+    assert.equal((await unflagged.json()).from_alternatives, undefined);
+});
@@ -0,0 +1,27 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+test('batched /detect reports the method and confidence per item', async () => {
+    const res = await server.post('/detect', { text: ['こんにちは、元気ですか', '안녕하세요', 'The weather is really nice today, so we went for a walk.'] });
+    assert.equal(res.status, 200);
+    const { results } = await res.json();
+    assert.deepEqual(results.map(({ language, method }) => ({ language, method })), [
+        { language: 'jp', method: 'script' },
+        { language: 'ko', method: 'script' },
+        { language: 'en', method: 'statistical' },
+    ]);
+    // Every letter of the kana/kanji and Hangul strings belongs to the detected script
+    assert.equal(results[0].confidence, 1);
+    assert.equal(results[1].confidence, 1);
+    assert.ok(results[2].confidence >= 0 && results[2].confidence <= 1);
+});
+
+test('a single /detect text keeps the plain shape', async () => {
+    const res = await server.post('/detect', { text: 'The weather is really nice today.' });
+    assert.deepEqual(await res.json(), { language: 'en' });
+});
@@ -0,0 +1,61 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import crypto from 'node:crypto';
+import net from 'node:net';
+import { startServer } from './helpers.js';
+
+// detectCacheHitRatio from the first /ws/status snapshot
+async function detectCacheHitRatio(server) {
+    const socket = net.connect(server.port, '127.0.0.1');
+    socket.write('GET /ws/status HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n'
+        + `Sec-WebSocket-Key: ${crypto.randomBytes(16).toString('base64')}\r\nSec-WebSocket-Version: 13\r\n\r\n`);
+    let buffer = Buffer.alloc(0);
+    try {
+        for (;;) {
+            const [chunk] = await Promise.race([
+                new Promise(resolve => socket.once('data', (...args) => resolve(args))),
+                new Promise((_, reject) => setTimeout(() => reject(new Error('no status snapshot')), 3000).unref()),
+            ]);
+            buffer = Buffer.concat([buffer, chunk]);
+            const frame = buffer.subarray(buffer.indexOf('\r\n\r\n') + 4);
+            if (frame.length < 4) continue;
+            // Snapshots are unmasked text frames with a 16-bit extended length
+            const length = frame[1] === 126 ? frame.readUInt16BE(2) : frame[1];
+            const start = frame[1] === 126 ? 4 : 2;
+            if (frame.length < start + length) continue;
+            return JSON.parse(frame.toString('utf8', start, start + length)).detectCacheHitRatio;
+        }
+    } finally {
+        socket.destroy();
+    }
+}
+
+const french = "Bonjour tout le monde, comment allez-vous aujourd'hui ?";
+const chinese = '今天天气很好，我们去公园散步吧。';
+
+test('detecting the same text again is served from the cache', async () => {
+    const server = await startServer();
+    try {
+        assert.equal(await detectCacheHitRatio(server), null);
+        for (let i = 0; i < 2; i++) {
+            assert.equal((await (await server.post('/detect', { text: chinese })).json()).language, 'zh');
+        }
+        // One miss that ran the detector, then one hit
+        assert.equal(await detectCacheHitRatio(server), 0.5);
+
+        await server.post('/detect', { text: french });
+        assert.equal(await detectCacheHitRatio(server), 1 / 3);
+    } finally {
+        await server.stop();
+    }
+});
+
+test('DETECT_CACHE_SIZE=0 disables the cache', async () => {
+    const server = await startServer({ env: { DETECT_CACHE_SIZE: '0' } });
+    try {
+        for (let i = 0; i < 2; i++) await server.post('/detect', { text: chinese });
+        assert.equal(await detectCacheHitRatio(server), null);
+    } finally {
+        await server.stop();
+    }
+});
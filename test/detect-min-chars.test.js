@@ -0,0 +1,29 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+async function withServer(options, fn) {
+    const server = await startServer(options);
+    try {
+        await fn(server);
+    } finally {
+        await server.stop();
+    }
+}
+
+test('short text uses the only registered source language instead of detection', () => withServer({ pairs: ['en-zh'] }, async (server) => {
+    // Detection would call this Chinese; with en as the only source it is translated from en
+    const res = await server.post('/translate', { text: '你好', to: 'zh' });
+    assert.deepEqual(await res.json(), { text: '[zh] 你好', from: 'en', to: 'zh' });
+}));
+
+test('short text uses DEFAULT_SOURCE_LANG when set', () => withServer({ env: { DEFAULT_SOURCE_LANG: 'zh' } }, async (server) => {
+    const res = await server.post('/translate', { text: 'OK', to: 'en' });
+    assert.deepEqual(await res.json(), { text: '[en] OK', from: 'zh', to: 'en' });
+}));
+
+test('text at DETECT_MIN_CHARS or longer is detected', () => withServer({ pairs: ['en-zh'], env: { DETECT_MIN_CHARS: '4' } }, async (server) => {
+    // Detected as Chinese, which is already the target
+    const res = await server.post('/translate', { text: '今天天气很好', to: 'zh' });
+    assert.deepEqual(await res.json(), { text: '今天天气很好', from: 'zh', to: 'zh', skipped: true });
+}));
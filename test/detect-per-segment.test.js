@@ -0,0 +1,39 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ pairs: ['en-zh', 'jp-zh'] }); });
+after(() => server.stop());
+
+const text = 'The weather is lovely today.\n\nきょうはいいてんきですね。';
+
+test('detect_per_segment translates each paragraph from its own detected source', async () => {
+    const res = await server.post('/translate/document', { text, to: 'zh', detect_per_segment: true });
+    assert.equal(res.status, 200);
+    const body = await res.json();
+    assert.deepEqual(body.segment_sources, ['en', 'jp']);
+    assert.equal(body.text, '[zh] The weather is lovely today.\n\n[zh] きょうはいいてんきですね。');
+    assert.match(server.output(), /Creating WASM instance for model: jp-zh/);
+});
+
+test('a paragraph already in the target language is left as it is', async () => {
+    const res = await server.post('/translate/document', { text: 'Hello there, friend.\n\n今天天气很好', to: 'zh', detect_per_segment: true });
+    const body = await res.json();
+    assert.deepEqual(body.segment_sources, ['en', 'zh']);
+    assert.equal(body.text, '[zh] Hello there, friend.\n\n今天天气很好');
+});
+
+test('without detect_per_segment the document is detected once', async () => {
+    // The kana outweigh the English, so the whole document goes through jp-zh
+    const res = await server.post('/translate/document', { text, to: 'zh' });
+    const body = await res.json();
+    assert.equal(body.from, 'jp');
+    assert.equal(body.segment_sources, undefined);
+    assert.equal(body.text, '[zh] The weather is lovely today.\n\n[zh] きょうはいいてんきですね。');
+});
+
+test('an explicit from turns per-segment detection off', async () => {
+    const res = await server.post('/translate/document', { text, from: 'en', to: 'zh', detect_per_segment: true });
+    assert.equal((await res.json()).segment_sources, undefined);
+});
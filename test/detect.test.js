@@ -0,0 +1,28 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => {
+    // fr-en starts without its model file, so it fails to preload and is not a detectable source
+    server = await startServer({
+        pairs: ['en-zh', 'fr-en'],
+        env: { DETECT_LOADED_SOURCES_ONLY: '1' },
+        prepare: modelsDir => fs.rm(path.join(modelsDir, 'fr-en', 'model.intgemm8.bin')),
+    });
+});
+after(() => server.stop());
+
+const french = "Bonjour tout le monde, comment allez-vous aujourd'hui ? Je suis très content de vous voir.";
+
+test('cached detections follow changes to the detectable sources', async () => {
+    assert.equal((await (await server.post('/detect', { text: french })).json()).language, 'en');
+
+    // Restore the file; loading the pair clears its load error and makes fr detectable
+    await fs.writeFile(path.join(server.modelsDir, 'fr-en', 'model.intgemm8.bin'), 'stub');
+    assert.equal((await server.post('/translate', { text: 'Bonjour', from: 'fr', to: 'en' })).status, 200);
+
+    assert.equal((await (await server.post('/detect', { text: french })).json()).language, 'fr');
+});
@@ -0,0 +1,24 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ env: { VERIFY_DETERMINISM: '1' } }); });
+after(() => server.stop());
+
+test('verify_determinism reports whether two translations matched', async () => {
+    const stable = await (await server.post('/translate', { text: 'Hello world', from: 'en', to: 'zh', verify_determinism: true })).json();
+    assert.equal(stable.deterministic, true);
+    const unstable = await (await server.post('/translate', { text: 'RANDOM output', from: 'en', to: 'zh', verify_determinism: true })).json();
+    assert.equal(unstable.deterministic, false);
+});
+
+test('deterministic is only reported when asked for', async () => {
+    const body = await (await server.post('/translate', { text: 'Hello world', from: 'en', to: 'zh' })).json();
+    assert.equal('deterministic' in body, false);
+});
+
+test('VERIFY_DETERMINISM logs differing outputs', async () => {
+    await server.post('/translate', { text: 'RANDOM again', from: 'en', to: 'zh' });
+    assert.match(server.output(), /Nondeterministic output from en-zh for \d+ char input: /);
+});
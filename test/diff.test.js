@@ -0,0 +1,40 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+// The previous translation is written by hand, so anything the stub model translated again
+// shows up with its "[zh] " prefix
+const previous = {
+    previous_source: 'The cat sat. The dog ran. The bird sang.\n\nRain fell.',
+    previous_translation: 'OLD cat. OLD dog. OLD bird.\n\nOLD rain.',
+    from: 'en',
+    to: 'zh',
+};
+
+test('only the edited sentence is sent to the model', async () => {
+    const res = await server.post('/translate/diff', { ...previous, source: 'The cat sat. The dog walked. The bird sang.\n\nRain fell.' });
+    assert.equal(res.status, 200);
+    assert.equal(res.headers.get('x-reused-segments'), '3');
+    assert.deepEqual(await res.json(), {
+        text: 'OLD cat. [zh] The dog walked. OLD bird.\n\nOLD rain.',
+        from: 'en',
+        to: 'zh',
+        translated_segments: [1],
+    });
+});
+
+test('new paragraphs and moved sentences', async () => {
+    const res = await server.post('/translate/diff', { ...previous, source: 'Rain fell.\n\nThe bird sang. Snow came.\n\nThe cat sat.' });
+    const body = await res.json();
+    assert.equal(body.text, 'OLD rain.\n\nOLD bird. [zh] Snow came.\n\nOLD cat.');
+    assert.deepEqual(body.translated_segments, [2]);
+});
+
+test('paragraph counts must match', async () => {
+    const res = await server.post('/translate/diff', { ...previous, previous_translation: 'OLD', source: 'Rain fell.' });
+    assert.equal(res.status, 400);
+});
@@ -0,0 +1,47 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+test('paragraphs are reassembled in order with their exact separators', async () => {
+    const paragraphs = ['First paragraph.', 'Second one\nhas two lines.', '  Indented third.', 'Fourth.', 'Last, with a trailing newline.'];
+    const separators = ['\n\n', '\r\n\r\n', '\n \t\n\n\n', '\n\n', '\n'];
+    const text = paragraphs.map((p, i) => p + separators[i]).join('');
+    const res = await server.post('/translate/document', { text, from: 'en', to: 'zh' });
+    assert.equal(res.status, 200);
+    assert.equal(res.headers.get('x-chunk-count'), '5');
+    assert.equal(res.headers.get('x-total-chars'), String(text.length));
+    const expected = paragraphs.map((p, i) => p.replace(/^(\s*)/, '$1[zh] ') + separators[i]).join('');
+    assert.equal((await res.json()).text, expected);
+});
+
+test('segmented: true pairs each source paragraph with its translation', async () => {
+    const res = await server.post('/translate/document', { text: 'One\n\nTwo', from: 'en', to: 'zh', segmented: true });
+    assert.deepEqual((await res.json()).segments, [
+        { source: 'One', target: '[zh] One' },
+        { source: 'Two', target: '[zh] Two' },
+    ]);
+});
+
+test('CRLF blank lines split paragraphs like LF ones', async () => {
+    const text = 'First paragraph. It has two sentences.\r\n\r\nSecond paragraph.\r\n\r\n\r\nThird paragraph.';
+    const res = await server.post('/translate/document', { text, from: 'en', to: 'zh' });
+    assert.equal(res.headers.get('x-chunk-count'), '3');
+    assert.equal((await res.json()).text,
+        '[zh] First paragraph. It has two sentences.\r\n\r\n[zh] Second paragraph.\r\n\r\n\r\n[zh] Third paragraph.');
+});
+
+test('segments line up one-to-one with the paragraph split', async () => {
+    const text = 'Title\n\nFirst body paragraph.\r\n\r\n  Indented quote.\n\n\nClosing line.';
+    const res = await server.post('/translate/document', { text, from: 'en', to: 'zh', segmented: true });
+    const body = await res.json();
+    assert.equal(body.segments.length, Number(res.headers.get('x-chunk-count')));
+    // The sources and targets rejoined with the document's separators give the input and output
+    const separators = text.match(/\r?\n[ \t]*\r?\n\s*/g);
+    assert.equal(body.segments.map((s, i) => s.source + (separators[i] ?? '')).join(''), text);
+    assert.equal(body.segments.map((s, i) => s.target + (separators[i] ?? '')).join(''), body.text);
+    for (const { source, target } of body.segments) assert.equal(target, source.replace(/^(\s*)/, '$1[zh] '));
+});
@@ -0,0 +1,125 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { spawn } from 'node:child_process';
+import { once } from 'node:events';
+import crypto from 'node:crypto';
+import fs from 'node:fs/promises';
+import http from 'node:http';
+import os from 'node:os';
+import path from 'node:path';
+import zlib from 'node:zlib';
+import { ROOT } from './helpers.js';
+
+// One en-zh model of three files. The model file's first response is cut off halfway, so the
+// download only completes by resuming with a Range request; corrupt.bin.gz doesn't match the
+// hash models.json lists for it.
+const contents = {
+    'model.enzh.intgemm8.bin': crypto.randomBytes(64 * 1024),
+    'lex.50.50.enzh.s2t.bin': Buffer.from('lexical shortlist'),
+    'vocab.enzh.spm': Buffer.from('shared vocabulary'),
+};
+const gzipped = Object.fromEntries(Object.entries(contents).map(([name, data]) => [`${name}.gz`, zlib.gzipSync(data)]));
+gzipped['corrupt.bin.gz'] = zlib.gzipSync(Buffer.from('not the model'));
+const sha256 = data => crypto.createHash('sha256').update(data).digest('hex');
+const fileInfo = (name, data = contents[name]) => ({ path: `enzh/${name}.gz`, uncompressedHash: sha256(data), uncompressedSize: data.length });
+
+let server;
+let baseUrl;
+const requests = [];
+let interrupted = false;
+
+function registry(modelFile) {
+    return {
+        generated: '2026-01-01T00:00:00Z',
+        baseUrl: `${baseUrl}/files`,
+        models: {
+            'en-zh': [{
+                sourceLanguage: 'en',
+                targetLanguage: 'zh',
+                architecture: 'tiny',
+                files: {
+                    model: modelFile,
+                    lexicalShortlist: fileInfo('lex.50.50.enzh.s2t.bin'),
+                    vocab: fileInfo('vocab.enzh.spm'),
+                },
+            }],
+        },
+    };
+}
+
+before(async () => {
+    server = http.createServer((req, res) => {
+        requests.push({ url: req.url, range: req.headers.range });
+        if (req.url === '/models.json' || req.url === '/corrupt.json') {
+            const modelFile = req.url === '/models.json'
+                ? fileInfo('model.enzh.intgemm8.bin')
+                : { ...fileInfo('model.enzh.intgemm8.bin'), path: 'enzh/corrupt.bin.gz' };
+            return res.end(JSON.stringify(registry(modelFile)));
+        }
+        const data = gzipped[path.basename(req.url)];
+        if (!data) return res.writeHead(404).end();
+
+        const range = /^bytes=(\d+)-$/.exec(req.headers.range || '');
+        if (range) {
+            const start = Number(range[1]);
+            res.writeHead(206, { 'Content-Length': data.length - start, 'Content-Range': `bytes ${start}-${data.length - 1}/${data.length}` });
+            return res.end(data.subarray(start));
+        }
+        res.writeHead(200, { 'Content-Length': data.length });
+        if (req.url.endsWith('model.enzh.intgemm8.bin.gz') && !interrupted) {
+            interrupted = true;
+            res.write(data.subarray(0, data.length / 2), () => res.destroy());
+            return;
+        }
+        res.end(data);
+    });
+    server.listen(0, '127.0.0.1');
+    await once(server, 'listening');
+    baseUrl = `http://127.0.0.1:${server.address().port}`;
+});
+after(() => server.close());
+
+async function download(registryPath) {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'linguaspark-download-'));
+    const child = spawn(process.execPath, [path.join(ROOT, 'download-models.js'), '--model=en-zh', `--dir=${dir}`], {
+        cwd: ROOT,
+        env: { PATH: process.env.PATH, MODELS_JSON_URL: `${baseUrl}${registryPath}` },
+        stdio: ['ignore', 'pipe', 'pipe'],
+    });
+    let output = '';
+    child.stdout.on('data', chunk => { output += chunk; });
+    child.stderr.on('data', chunk => { output += chunk; });
+    const [code] = await once(child, 'exit');
+    return { code, output, dir };
+}
+
+test('an interrupted download resumes with a Range request and passes its checksum', async () => {
+    const { code, output, dir } = await download('/models.json');
+    try {
+        assert.equal(code, 0, output);
+        assert.match(output, /Retry 1\/3 for model\.enzh\.intgemm8\.bin\.gz/);
+        assert.match(output, /Verified: model\.enzh\.intgemm8\.bin/);
+
+        const modelRequests = requests.filter(r => r.url.endsWith('model.enzh.intgemm8.bin.gz'));
+        assert.equal(modelRequests.length, 2);
+        assert.equal(modelRequests[0].range, undefined);
+        assert.match(modelRequests[1].range, /^bytes=[1-9]\d*-$/);
+
+        for (const [name, data] of Object.entries(contents)) {
+            assert.ok((await fs.readFile(path.join(dir, 'en-zh', name))).equals(data), name);
+        }
+    } finally {
+        await fs.rm(dir, { recursive: true, force: true });
+    }
+});
+
+test('a file that fails its checksum is deleted and the download fails', async () => {
+    const { code, output, dir } = await download('/corrupt.json');
+    try {
+        assert.equal(code, 1, output);
+        assert.match(output, /Failed to download en-zh: Checksum mismatch for corrupt\.bin/);
+        await assert.rejects(fs.access(path.join(dir, 'en-zh', 'corrupt.bin')));
+    } finally {
+        await fs.rm(dir, { recursive: true, force: true });
+    }
+});
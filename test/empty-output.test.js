@@ -0,0 +1,20 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+test('an empty translation is retried and the retry is returned', async () => {
+    const res = await server.post('/translate', { text: 'BLANKONCE hello', from: 'en', to: 'zh' });
+    assert.equal((await res.json()).text, '[zh] BLANKONCE hello');
+    assert.doesNotMatch(server.output(), /Empty translation/);
+});
+
+test('still empty after the retry: the source is passed through with a warning', async () => {
+    const res = await server.post('/translate', { text: 'BLANK hello', from: 'en', to: 'zh' });
+    assert.equal(res.status, 200);
+    assert.equal((await res.json()).text, 'BLANK hello');
+    assert.match(server.output(), /Empty translation from en-zh for 11 char input after retry, returning source text/);
+});
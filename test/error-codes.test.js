@@ -0,0 +1,80 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import crypto from 'node:crypto';
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => {
+    server = await startServer({
+        pairs: ['en-zh', 'zh-en', 'en-ja', 'en-ko'],
+        env: { API_KEY: 'secret', PAIR_RATE_LIMITS: 'zhen:1', MAX_BATCH_SIZE: '2' },
+        async prepare(modelsDir) {
+            // en-ja is missing a file; en-ko's checksums.txt doesn't match its model
+            await fs.rm(path.join(modelsDir, 'en-ja', 'lex.s2t.bin'));
+            const wrong = crypto.createHash('sha256').update('not the model').digest('hex');
+            await fs.writeFile(path.join(modelsDir, 'en-ko', 'checksums.txt'), `${wrong}  model.intgemm8.bin\n`);
+        },
+    });
+});
+after(() => server.stop());
+
+const auth = { Authorization: 'Bearer secret' };
+
+async function expectError(res, status, code) {
+    assert.equal(res.status, status);
+    const body = await res.json();
+    assert.equal(body.code, code, JSON.stringify(body));
+    assert.equal(typeof body.error, 'string');
+}
+
+test('UNAUTHORIZED', async () => {
+    await expectError(await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' }), 401, 'UNAUTHORIZED');
+});
+
+test('INVALID_REQUEST', async () => {
+    await expectError(await server.post('/translate', { from: 'en', to: 'zh' }, auth), 400, 'INVALID_REQUEST');
+});
+
+test('MODEL_NOT_AVAILABLE', async () => {
+    await expectError(await server.post('/translate', { text: 'Hallo', from: 'de', to: 'zh' }, auth), 400, 'MODEL_NOT_AVAILABLE');
+});
+
+test('BATCH_TOO_LARGE', async () => {
+    const res = await server.post('/translate_mtranserver/batch', { texts: ['a', 'b', 'c'], from: 'en', to: 'zh' }, auth);
+    await expectError(res, 400, 'BATCH_TOO_LARGE');
+});
+
+test('RATE_LIMITED', async () => {
+    assert.equal((await server.post('/translate', { text: '你好', from: 'zh', to: 'en' }, auth)).status, 200);
+    await expectError(await server.post('/translate', { text: '你好', from: 'zh', to: 'en' }, auth), 429, 'RATE_LIMITED');
+});
+
+test('NOT_FOUND', async () => {
+    await expectError(await server.post('/jobs/no-such-job/cancel', {}, auth), 404, 'NOT_FOUND');
+});
+
+test('MODEL_LOAD_FAILED', async () => {
+    await expectError(await server.post('/translate', { text: 'Hello', from: 'en', to: 'ja' }, auth), 500, 'MODEL_LOAD_FAILED');
+});
+
+test('MODEL_CHECKSUM_MISMATCH', async () => {
+    await expectError(await server.post('/translate', { text: 'Hello', from: 'en', to: 'ko' }, auth), 500, 'MODEL_CHECKSUM_MISMATCH');
+});
+
+test('TRANSLATION_FAILED', async () => {
+    await expectError(await server.post('/translate', { text: 'FAIL here', from: 'en', to: 'zh' }, auth), 500, 'TRANSLATION_FAILED');
+});
+
+test('TRANSLATOR_UNAVAILABLE', async () => {
+    const res = await server.post('/translate', { text: 'CRASH here', from: 'en', to: 'zh' }, auth);
+    assert.equal(res.headers.get('retry-after'), '1');
+    await expectError(res, 502, 'TRANSLATOR_UNAVAILABLE');
+});
+
+test('a response count that does not match the inputs is a clear error', async () => {
+    const res = await server.post('/translate', { text: 'NORESULT here', from: 'en', to: 'zh' }, auth);
+    assert.equal(res.status, 500);
+    assert.deepEqual(await res.json(), { error: 'Bergamot returned 0 results for 1 inputs', code: 'TRANSLATION_FAILED' });
+});
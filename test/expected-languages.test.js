@@ -0,0 +1,30 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+const french = "Bonjour tout le monde, comment allez-vous aujourd'hui ? Je suis très content de vous voir.";
+// Kanji only: as readily Chinese as Japanese
+const kanji = '東京大学図書館';
+
+async function detect(env, text) {
+    const server = await startServer({ env });
+    try {
+        return (await (await server.post('/detect', { text })).json()).language;
+    } finally {
+        await server.stop();
+    }
+}
+
+test('detection without EXPECTED_LANGUAGES is unrestricted', async () => {
+    assert.equal(await detect({}, french), 'fr');
+});
+
+test('EXPECTED_LANGUAGES keeps detection within the set', async () => {
+    // Japanese is reported as jp, the code the model directories use
+    assert.ok(['en', 'zh', 'jp'].includes(await detect({ EXPECTED_LANGUAGES: 'en,zh,ja' }, french)));
+});
+
+test('a Han-script text resolves to the allowed CJK language', async () => {
+    assert.equal(await detect({ EXPECTED_LANGUAGES: 'ja,en' }, kanji), 'jp');
+    assert.equal(await detect({ EXPECTED_LANGUAGES: 'zh,en' }, kanji), 'zh');
+});
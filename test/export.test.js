@@ -0,0 +1,60 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+// Minimal well-formedness check (Node has no XML parser): one root, properly nested and closed
+// elements, quoted attributes, and only the predefined or numeric entity references
+function assertWellFormedXml(xml) {
+    const body = xml.replace(/^<\?xml[^?]*\?>\s*/, '');
+    const stack = [];
+    let roots = 0;
+    for (const [, text, tag] of body.matchAll(/([^<]*)(<[^>]*>|$)/g)) {
+        assert.doesNotMatch(text, /&(?!(?:amp|lt|gt|quot|apos|#\d+|#x[\da-f]+);)/i, `bad entity in ${JSON.stringify(text)}`);
+        if (text.trim()) assert.ok(stack.length > 0, 'text outside the root element');
+        if (!tag) continue;
+        const m = tag.match(/^<(\/?)([A-Za-z_][\w.:-]*)((?:\s+[\w.:-]+="[^"<]*")*)\s*(\/?)>$/);
+        assert.ok(m, `malformed tag ${tag}`);
+        const [, closing, name, , selfClosing] = m;
+        if (closing) {
+            assert.equal(stack.pop(), name, `mismatched </${name}>`);
+        } else {
+            if (stack.length === 0) roots++;
+            if (!selfClosing) stack.push(name);
+        }
+    }
+    assert.deepEqual(stack, [], 'unclosed elements');
+    assert.equal(roots, 1, 'expected exactly one root element');
+}
+
+test('TMX export is well-formed and holds one unit per distinct translation', async () => {
+    await server.post('/translate', { text: 'Fish & <chips>', from: 'en', to: 'zh' });
+    await server.post('/translate', { text: 'Fish & <chips>', from: 'en', to: 'zh' });
+    await server.post('/translate', { text: '你好世界', from: 'zh', to: 'en' });
+
+    const res = await server.fetch('/cache/export?format=tmx');
+    assert.equal(res.status, 200);
+    assert.match(res.headers.get('content-type'), /application\/x-tmx\+xml/);
+    const tmx = await res.text();
+
+    assertWellFormedXml(tmx);
+    assert.match(tmx, /^<\?xml version="1.0" encoding="UTF-8"\?>\n<tmx version="1.4">/);
+    const units = [...tmx.matchAll(/<tu>\s*<prop type="x-pair">([^<]*)<\/prop>\s*<tuv xml:lang="([^"]*)"><seg>([^<]*)<\/seg><\/tuv>\s*<tuv xml:lang="([^"]*)"><seg>([^<]*)<\/seg><\/tuv>\s*<\/tu>/g)]
+        .map(m => m.slice(1));
+    assert.deepEqual(units, [
+        ['en-zh', 'en', 'Fish &amp; &lt;chips&gt;', 'zh', '[zh] Fish &amp; &lt;chips&gt;'],
+        ['zh-en', 'zh', '你好世界', 'en', '[en] 你好世界'],
+    ]);
+});
+
+test('/monitor/export serves the same file', async () => {
+    const [cache, monitor] = await Promise.all(['/cache/export', '/monitor/export'].map(async route => (await server.fetch(route)).text()));
+    assert.equal(monitor, cache);
+});
+
+test('unsupported export format is a 400', async () => {
+    assert.equal((await server.fetch('/cache/export?format=csv')).status, 400);
+});
@@ -0,0 +1,76 @@
+// Stand-in for wasm/bergamot-translator.js used by the test suite. It exposes the same embind
+// surface server.js uses, and "translates" by prefixing the target language: "[zh] Hello".
+// Input containing FAIL throws an ordinary error, input containing CRASH traps like a real WASM
+// fault, input containing NORESULT gets no response back (a miscounted batch), input containing
+// RANDOM gets a different output every time, input containing SLOW takes 100 ms, input containing
+// BLANK comes back empty (BLANKONCE only the first time it is seen), as does everything for a
+// model whose config contains `stub-blank: true` (a broken model), input containing LOOP is
+// repeated ten times like a runaway model, SHOWCONFIG is answered with the Marian config the model
+// was created with, thousands separators are dropped (1,000 -> 1000) and `{0}` is spaced out to
+// `{ 0 }` as real models do, `<<n>>` placeholders are dropped (a format the "model" doesn't
+// survive), "Close" reads as the verb "Close(v)" when the input mentions a button (context), and a
+// deleted TranslationModel rejects further use with Emscripten's error, so segment failures,
+// nondeterminism, latency budgets, blank output, runaway output, configuration, entity and
+// placeholder leaks and crash recovery can be exercised without the engine.
+function loadBergamot(Module) {
+    class Vector {
+        constructor() { this.items = []; }
+        push_back(item) { this.items.push(item); }
+        size() { return this.items.length; }
+        get(i) { return this.items[i]; }
+        delete() {}
+    }
+
+    Module.AlignedMemory = class {
+        constructor(size) { this.bytes = new Uint8Array(size); }
+        getByteArrayView() { return this.bytes; }
+    };
+    Module.AlignedMemoryList = Vector;
+    Module.VectorString = Vector;
+    Module.VectorResponseOptions = Vector;
+
+    Module.TranslationModel = class {
+        constructor(from, to, config) {
+            this.from = from;
+            this.to = to;
+            this.config = config;
+            this.deleted = false;
+        }
+        delete() { this.deleted = true; }
+    };
+
+    const blankedOnce = new Set();
+    Module.BlockingService = class {
+        translate(model, messages) {
+            if (model.deleted) throw new Error('Cannot pass deleted object as a pointer of type TranslationModel');
+            const responses = new Vector();
+            for (const text of messages.items) {
+                if (text.includes('FAIL')) throw new Error('segment rejected');
+                if (text.includes('CRASH')) throw new WebAssembly.RuntimeError('unreachable');
+                if (text.includes('NORESULT')) continue;
+                if (text.includes('SLOW')) {
+                    const until = Date.now() + 100;
+                    while (Date.now() < until); // the real engine blocks the event loop too
+                }
+                const blank = text.includes('BLANKONCE') ? !blankedOnce.has(text) && blankedOnce.add(text) : text.includes('BLANK');
+                if (blank || model.config?.includes('stub-blank: true')) {
+                    responses.push_back({ getTranslatedText: () => '' });
+                    continue;
+                }
+                if (text.includes('SHOWCONFIG')) {
+                    responses.push_back({ getTranslatedText: () => model.config });
+                    continue;
+                }
+                let output = text.replace(/<<\d+>>/g, '').replace(/(\d),(\d{3})\b/g, '$1$2').replace(/\{(\d+)\}/g, '{ $1 }');
+                if (/\bbutton\b/.test(output)) output = output.replace(/\bClose\b/g, 'Close(v)');
+                if (text.includes('LOOP')) output = Array(10).fill(output).join(' ');
+                const translated = text.includes('RANDOM') ? `[${model.to}] ${Math.random()}` : `[${model.to}] ${output}`;
+                responses.push_back({ getTranslatedText: () => translated });
+            }
+            return responses;
+        }
+        delete() {}
+    };
+
+    Promise.resolve().then(() => Module.onRuntimeInitialized.call(Module));
+}
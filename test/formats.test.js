@@ -0,0 +1,55 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+test('CSV: only the chosen columns are translated, quoting is kept', async () => {
+    const csv = 'id,name,note\n1,Hello,"Good, morning"\n2,World,plain\n';
+    const res = await server.fetch('/translate/csv?from=en&to=zh&columns=note', {
+        method: 'POST',
+        headers: { 'Content-Type': 'text/csv' },
+        body: csv,
+    });
+    assert.equal(res.status, 200);
+    assert.equal(res.headers.get('x-translated-cells'), '2');
+    assert.equal(await res.text(), 'id,name,note\n1,Hello,"[zh] Good, morning"\n2,World,[zh] plain\n');
+});
+
+test('CSV: unknown column is a 400', async () => {
+    const res = await server.post('/translate/csv', { csv: 'a,b\n1,2\n', from: 'en', to: 'zh', columns: ['c'] });
+    assert.equal(res.status, 400);
+});
+
+test('KV text: values are translated, keys and comments are kept', async () => {
+    const res = await server.fetch('/translate/kv?from=en&to=zh', {
+        method: 'POST',
+        headers: { 'Content-Type': 'text/plain' },
+        body: '# greeting\nhello=Hello\nbye = Goodbye\n',
+    });
+    assert.equal(res.status, 200);
+    assert.equal(res.headers.get('x-translated-values'), '2');
+    assert.equal(await res.text(), '# greeting\nhello=[zh] Hello\nbye = [zh] Goodbye\n');
+});
+
+test('KV object', async () => {
+    const res = await server.post('/translate/kv', { kv: { title: 'Welcome', empty: '' }, from: 'en', to: 'zh' });
+    assert.deepEqual(await res.json(), { kv: { title: '[zh] Welcome', empty: '' }, from: 'en', to: 'zh' });
+});
+
+test('Accept: text/plain gets the bare translation', async () => {
+    const res = await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' }, { Accept: 'text/plain' });
+    assert.equal(res.status, 200);
+    assert.match(res.headers.get('content-type'), /^text\/plain/);
+    assert.equal(await res.text(), '[zh] Hello');
+});
+
+test('Accept: application/json and no Accept get the JSON shape', async () => {
+    for (const headers of [{ Accept: 'application/json' }, {}]) {
+        const res = await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' }, headers);
+        assert.match(res.headers.get('content-type'), /^application\/json/);
+        assert.deepEqual(await res.json(), { text: '[zh] Hello', from: 'en', to: 'zh' });
+    }
+});
@@ -0,0 +1,88 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+import { startServer } from './helpers.js';
+
+const auth = { Authorization: 'Bearer secret' };
+const tmp = fs.mkdtempSync(path.join(os.tmpdir(), 'glossary-'));
+const glossaryFile = path.join(tmp, 'glossary.json');
+fs.writeFileSync(glossaryFile, JSON.stringify({ Huawei: '华为' }));
+
+let server;
+before(async () => { server = await startServer({ env: { API_KEY: 'secret', GLOSSARY_FILE: glossaryFile } }); });
+after(async () => {
+    await server.stop();
+    fs.rmSync(tmp, { recursive: true, force: true });
+});
+
+function put(term, target, query = '') {
+    return server.fetch(`/glossary/${encodeURIComponent(term)}${query}`, {
+        method: 'PUT',
+        headers: { 'Content-Type': 'application/json', ...auth },
+        body: JSON.stringify({ target }),
+    });
+}
+
+function remove(term, query = '') {
+    return server.fetch(`/glossary/${encodeURIComponent(term)}${query}`, { method: 'DELETE', headers: auth });
+}
+
+async function translate(text) {
+    const res = await server.post('/translate', { text, from: 'en', to: 'zh' }, auth);
+    return (await res.json()).text;
+}
+
+test('GET /glossary lists the terms loaded from GLOSSARY_FILE', async () => {
+    const res = await server.fetch('/glossary', { headers: auth });
+    assert.deepEqual(await res.json(), { glossary: { Huawei: '华为' }, count: 1 });
+    assert.equal(await translate('Huawei phones'), '[zh] 华为 phones');
+});
+
+test('the glossary endpoints require the key', async () => {
+    assert.equal((await server.fetch('/glossary')).status, 401);
+    assert.equal((await server.fetch('/glossary/Xiaomi', { method: 'DELETE' })).status, 401);
+});
+
+test('a term added with PUT is protected in the next translation, and DELETE undoes it', async () => {
+    assert.equal(await translate('Xiaomi phones'), '[zh] Xiaomi phones');
+
+    const added = await put('Xiaomi', '小米');
+    assert.deepEqual(await added.json(), { success: true, term: 'Xiaomi', target: '小米' });
+    assert.equal(await translate('Xiaomi phones'), '[zh] 小米 phones');
+
+    assert.equal((await remove('Xiaomi')).status, 200);
+    assert.equal(await translate('Xiaomi phones'), '[zh] Xiaomi phones');
+    // Without ?persist=1 the file is left alone
+    assert.deepEqual(JSON.parse(fs.readFileSync(glossaryFile, 'utf8')), { Huawei: '华为' });
+});
+
+test('?persist=1 writes the glossary back to GLOSSARY_FILE', async () => {
+    await put('Oppo', '欧珀', '?persist=1');
+    assert.deepEqual(JSON.parse(fs.readFileSync(glossaryFile, 'utf8')), { Huawei: '华为', Oppo: '欧珀' });
+    await remove('Huawei', '?persist=1');
+    assert.deepEqual(JSON.parse(fs.readFileSync(glossaryFile, 'utf8')), { Oppo: '欧珀' });
+});
+
+test('PUT needs a target and DELETE an existing term', async () => {
+    assert.equal((await put('Vivo', 42)).status, 400);
+    const res = await remove('Vivo');
+    assert.equal(res.status, 404);
+    assert.equal((await res.json()).code, 'NOT_FOUND');
+});
+
+test('?persist=1 without GLOSSARY_FILE is a 400', async () => {
+    const bare = await startServer({ env: { API_KEY: 'secret' } });
+    try {
+        const res = await bare.fetch('/glossary/Vivo?persist=1', {
+            method: 'PUT',
+            headers: { 'Content-Type': 'application/json', ...auth },
+            body: JSON.stringify({ target: '维沃' }),
+        });
+        assert.equal(res.status, 400);
+        assert.match((await res.json()).error, /persist requires GLOSSARY_FILE/);
+    } finally {
+        await bare.stop();
+    }
+});
@@ -0,0 +1,51 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+const glossary = { Huawei: '华为' };
+
+test('verify_glossary: a term the model translates is missing without protection', async () => {
+    const res = await server.post('/translate', { text: 'Huawei phones', from: 'en', to: 'zh', glossary, verify_glossary: true, protect_glossary: false });
+    assert.deepEqual(await res.json(), { text: '[zh] Huawei phones', from: 'en', to: 'zh', glossary_ok: false, missing: ['华为'] });
+});
+
+test('verify_glossary: the term is present with protection', async () => {
+    const res = await server.post('/translate', { text: 'Huawei phones', from: 'en', to: 'zh', glossary, verify_glossary: true });
+    assert.deepEqual(await res.json(), { text: '[zh] 华为 phones', from: 'en', to: 'zh', glossary_ok: true, missing: [] });
+});
+
+test('verify_glossary without any glossary is a 400', async () => {
+    const res = await server.post('/translate', { text: 'Huawei phones', from: 'en', to: 'zh', verify_glossary: true });
+    assert.equal(res.status, 400);
+});
+
+test('report_glossary lists each glossary term substituted and how often', async () => {
+    const terms = { Huawei: '华为', Honor: '荣耀', Xiaomi: '小米' };
+    const res = await server.post('/translate', { text: 'Huawei and Honor, then Huawei again', from: 'en', to: 'zh', glossary: terms, report_glossary: true });
+    const body = await res.json();
+    assert.equal(body.text, '[zh] 华为 and 荣耀, then 华为 again');
+    assert.deepEqual(body.glossary_applied, [
+        { term: 'Huawei', target: '华为', count: 2 },
+        { term: 'Honor', target: '荣耀', count: 1 },
+    ]);
+});
+
+test('report_glossary does not count a term whose placeholder the model lost', async () => {
+    // The stub drops <<n>> placeholders
+    const lossy = await startServer({ env: { PLACEHOLDER_FORMAT: '<<{n}>>' } });
+    try {
+        const res = await lossy.post('/translate', { text: 'Huawei phones', from: 'en', to: 'zh', glossary, report_glossary: true });
+        assert.deepEqual((await res.json()).glossary_applied, []);
+    } finally {
+        await lossy.stop();
+    }
+});
+
+test('glossary_applied is only reported when asked for', async () => {
+    const res = await server.post('/translate', { text: 'Huawei phones', from: 'en', to: 'zh', glossary });
+    assert.equal((await res.json()).glossary_applied, undefined);
+});
@@ -0,0 +1,57 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import { startServer } from './helpers.js';
+
+test('/health is ready once REQUIRED_PAIRS load, whatever the other pairs do', async () => {
+    // en-zh (required) and zh-en (not required) both start without their model file
+    const server = await startServer({
+        env: { REQUIRED_PAIRS: 'enzh' },
+        prepare: modelsDir => Promise.all(['en-zh', 'zh-en'].map(pair => fs.rm(path.join(modelsDir, pair, 'model.intgemm8.bin')))),
+    });
+    try {
+        const starting = await server.fetch('/health');
+        assert.equal(starting.status, 503);
+        assert.ok(Number(starting.headers.get('retry-after')) >= 1);
+        const body = await starting.json();
+        assert.equal(body.status, 'starting');
+        assert.deepEqual(body.requiredPairs, ['en-zh']);
+        assert.deepEqual(body.missingPairs, ['en-zh']);
+
+        // Restoring the required pair and loading it (a translation clears the load error) is
+        // enough; zh-en is still broken
+        await fs.writeFile(path.join(server.modelsDir, 'en-zh', 'model.intgemm8.bin'), 'stub');
+        assert.equal((await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' })).status, 200);
+        const ready = await server.fetch('/health');
+        assert.equal(ready.status, 200);
+        const readyBody = await ready.json();
+        assert.equal(readyBody.status, 'ok');
+        assert.deepEqual(readyBody.missingPairs, []);
+        assert.deepEqual(readyBody.unhealthyPairs.map(p => p.key), ['zh-en']);
+    } finally {
+        await server.stop();
+    }
+});
+
+test('a required pair without a model directory keeps /health unready', async () => {
+    const server = await startServer({ env: { REQUIRED_PAIRS: 'enzh,jaen' } });
+    try {
+        const res = await server.fetch('/health');
+        assert.equal(res.status, 503);
+        assert.deepEqual((await res.json()).missingPairs, ['ja-en']);
+    } finally {
+        await server.stop();
+    }
+});
+
+test('without REQUIRED_PAIRS /health is ready', async () => {
+    const server = await startServer();
+    try {
+        const res = await server.fetch('/health');
+        assert.equal(res.status, 200);
+        assert.equal((await res.json()).requiredPairs, undefined);
+    } finally {
+        await server.stop();
+    }
+});
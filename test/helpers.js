@@ -0,0 +1,99 @@
+// Shared setup for the node --test suite: runs server.js as a child process against the stub
+// Bergamot glue and a temporary MODELS_DIR of placeholder model files.
+import { spawn } from 'node:child_process';
+import { once } from 'node:events';
+import fs from 'node:fs/promises';
+import net from 'node:net';
+import os from 'node:os';
+import path from 'node:path';
+import { fileURLToPath } from 'node:url';
+
+export const ROOT = path.resolve(path.dirname(fileURLToPath(import.meta.url)), '..');
+export const STUB_GLUE = path.join(ROOT, 'test', 'fixtures', 'stub-bergamot.js');
+const STARTUP_TIMEOUT_MS = 10000;
+
+// One directory per pair ("en-zh"); the stub never reads the files, the loader only needs them
+export async function createModelsDir(pairs) {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'linguaspark-test-'));
+    for (const pair of pairs) {
+        const modelDir = path.join(dir, pair);
+        await fs.mkdir(modelDir);
+        for (const file of ['model.intgemm8.bin', 'lex.s2t.bin', 'vocab.xxen.spm']) {
+            await fs.writeFile(path.join(modelDir, file), 'stub');
+        }
+    }
+    return dir;
+}
+
+function freePort() {
+    return new Promise((resolve, reject) => {
+        const server = net.createServer();
+        server.on('error', reject);
+        server.listen(0, '127.0.0.1', () => {
+            const { port } = server.address();
+            server.close(() => resolve(port));
+        });
+    });
+}
+
+// Start server.js with `env` on top of a clean environment (the developer's API_KEY etc. are
+// not inherited). `prepare(modelsDir)` can adjust the model files first. Resolves once the
+// server answers /health.
+export async function startServer({ pairs = ['en-zh', 'zh-en'], env = {}, prepare = null } = {}) {
+    const modelsDir = await createModelsDir(pairs);
+    await prepare?.(modelsDir);
+    const port = await freePort();
+    const child = spawn(process.execPath, [path.join(ROOT, 'server.js')], {
+        cwd: ROOT,
+        env: {
+            PATH: process.env.PATH,
+            IP: '127.0.0.1',
+            PORT: String(port),
+            MODELS_DIR: modelsDir,
+            WASM_PATH: STUB_GLUE,
+            JS_PATH: STUB_GLUE,
+            ...env,
+        },
+        stdio: ['ignore', 'pipe', 'pipe'],
+    });
+    let output = '';
+    child.stdout.on('data', chunk => { output += chunk; });
+    child.stderr.on('data', chunk => { output += chunk; });
+
+    const url = `http://127.0.0.1:${port}`;
+    const started = Date.now();
+    for (;;) {
+        if (child.exitCode !== null) throw new Error(`server exited with ${child.exitCode}:\n${output}`);
+        if (Date.now() - started > STARTUP_TIMEOUT_MS) {
+            child.kill();
+            throw new Error(`server did not start within ${STARTUP_TIMEOUT_MS} ms:\n${output}`);
+        }
+        try {
+            await fetch(`${url}/health`);
+            break;
+        } catch {
+            await new Promise(resolve => setTimeout(resolve, 50));
+        }
+    }
+
+    return {
+        url,
+        port,
+        modelsDir,
+        output: () => output,
+        fetch: (route, init) => fetch(`${url}${route}`, init),
+        // POST a JSON body
+        post: (route, body, headers = {}) => fetch(`${url}${route}`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json', ...headers },
+            body: JSON.stringify(body),
+        }),
+        async stop() {
+            if (child.exitCode === null) {
+                child.kill();
+                await once(child, 'exit');
+            }
+            await fs.rm(modelsDir, { recursive: true, force: true });
+        },
+    };
+}
@@ -0,0 +1,60 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+const sleep = ms => new Promise(resolve => setTimeout(resolve, ms));
+const cancel = id => server.fetch(`/jobs/${id}/cancel`, { method: 'POST' });
+
+// The stub spends 100 ms on each SLOW segment
+const slowTexts = Array.from({ length: 20 }, (_, i) => `SLOW segment ${i}`);
+
+test('cancelling an NDJSON job by its X-Job-Id stops dispatch', async () => {
+    const res = await server.post('/translate/ndjson', { texts: slowTexts, from: 'en', to: 'zh' });
+    const id = res.headers.get('x-job-id');
+    assert.ok(id);
+
+    const reader = res.body.getReader();
+    const decoder = new TextDecoder();
+    let received = '';
+    while (!received.includes('\n')) received += decoder.decode((await reader.read()).value, { stream: true });
+    const cancelled = await cancel(id);
+    assert.deepEqual(await cancelled.json(), { id, path: '/translate/ndjson', cancelled: true });
+    for (let chunk = await reader.read(); !chunk.done; chunk = await reader.read()) received += decoder.decode(chunk.value, { stream: true });
+
+    const lines = received.trim().split('\n').map(line => JSON.parse(line));
+    const summary = lines.at(-1);
+    assert.equal(summary.done, true);
+    assert.equal(summary.cancelled, true);
+    assert.ok(summary.count < slowTexts.length, `dispatched ${summary.count} of ${slowTexts.length}`);
+    assert.equal(lines.length - 1, summary.count);
+    // The job is gone once its response closes
+    assert.equal((await cancel(id)).status, 404);
+});
+
+test('a /translate/document job cancelled by a client-chosen id answers 409', async () => {
+    const pending = server.post('/translate/document', { text: slowTexts.join('\n\n'), from: 'en', to: 'zh' }, { 'X-Job-Id': 'doc-1' });
+    await sleep(300);
+    assert.equal((await cancel('doc-1')).status, 200);
+    const res = await pending;
+    assert.equal(res.status, 409);
+    assert.equal(res.headers.get('x-job-id'), 'doc-1');
+    assert.equal((await res.json()).code, 'JOB_CANCELLED');
+});
+
+test('an id that is already running is rejected', async () => {
+    const pending = server.post('/translate/document', { text: slowTexts.slice(0, 10).join('\n\n'), from: 'en', to: 'zh' }, { 'X-Job-Id': 'doc-2' });
+    await sleep(200);
+    const duplicate = await server.post('/translate/document', { text: 'Hello', from: 'en', to: 'zh' }, { 'X-Job-Id': 'doc-2' });
+    assert.equal(duplicate.status, 400);
+    assert.equal((await pending).status, 200);
+});
+
+test('cancelling an unknown job is a 404', async () => {
+    const res = await cancel('no-such-job');
+    assert.equal(res.status, 404);
+    assert.equal((await res.json()).code, 'NOT_FOUND');
+});
@@ -0,0 +1,33 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+test('/kiss answers the Custom translator request with { text, from, to }', async () => {
+    const res = await server.post('/kiss', { text: 'Hello world', from: 'en', to: 'zh' });
+    assert.equal(res.status, 200);
+    assert.deepEqual(await res.json(), { text: '[zh] Hello world', from: 'en', to: 'zh' });
+});
+
+test('source_lang and target_lang are accepted, and "auto" is detected', async () => {
+    const res = await server.post('/kiss', { text: '你好世界', source_lang: 'auto', target_lang: 'en' });
+    assert.deepEqual(await res.json(), { text: '[en] 你好世界', from: 'zh', to: 'en' });
+});
+
+test('a missing source is detected too', async () => {
+    const res = await server.post('/kiss', { text: 'Hello world', to: 'zh' });
+    assert.deepEqual(await res.json(), { text: '[zh] Hello world', from: 'en', to: 'zh' });
+});
+
+test('text already in the target language is echoed back', async () => {
+    const res = await server.post('/kiss', { text: '你好世界', from: 'auto', to: 'zh' });
+    assert.deepEqual(await res.json(), { text: '你好世界', from: 'zh', to: 'zh' });
+});
+
+test('missing text or target is a 400', async () => {
+    assert.equal((await server.post('/kiss', { text: 'Hello', from: 'en' })).status, 400);
+    assert.equal((await server.post('/kiss', { text: ['Hello'], to: 'zh' })).status, 400);
+});
@@ -0,0 +1,55 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+import { startServer } from './helpers.js';
+
+let server;
+let manifestDir;
+before(async () => {
+    // Written by `prepare`, since the model directories only exist once startServer creates them
+    manifestDir = await fs.mkdtemp(path.join(os.tmpdir(), 'linguaspark-manifest-'));
+    const manifest = path.join(manifestDir, 'models.json');
+    server = await startServer({
+        pairs: ['en-zh', 'fr-en'],
+        env: { MODELS_MANIFEST: manifest },
+        prepare: modelsDir => fs.writeFile(manifest, JSON.stringify({
+            models: [
+                { from: 'en', to: 'zh', path: path.join(modelsDir, 'en-zh'), aliases: ['en-zh_Hans', 'en-yue'] },
+                { from: 'fr', to: 'en', path: path.join(modelsDir, 'fr-en') },
+            ],
+        })),
+    });
+});
+after(async () => {
+    await server.stop();
+    await fs.rm(manifestDir, { recursive: true, force: true });
+});
+
+test('aliases are listed with their model, not as models of their own', async () => {
+    const { models } = await (await server.fetch('/models')).json();
+    assert.deepEqual(models, [
+        { key: 'en-zh', from: 'en', to: 'zh', aliases: ['en-zh_Hans', 'en-yue'] },
+        { key: 'fr-en', from: 'fr', to: 'en' },
+    ]);
+});
+
+test('an alias and its model share the loaded instance', async () => {
+    for (const to of ['zh', 'zh_Hans', 'zh']) {
+        const res = await server.post('/translate', { text: 'Hello world', from: 'en', to });
+        assert.equal(res.status, 200, to);
+        assert.equal((await res.json()).text, '[zh] Hello world');
+    }
+    assert.equal(server.output().match(/Unloading previous model/g)?.length ?? 0, 0);
+});
+
+test('aliases take part in pivot routes', async () => {
+    const res = await server.post('/translate_mtranserver?debug=model', { text: 'Bonjour le monde', from: 'fr', to: 'yue' });
+    assert.equal(res.status, 200);
+    assert.deepEqual(await res.json(), {
+        result: '[zh] [en] Bonjour le monde',
+        pivot_path: ['fr', 'en', 'yue'],
+        model_used: ['fr-en', 'en-zh'],
+    });
+});
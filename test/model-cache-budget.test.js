@@ -0,0 +1,42 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+const cacheEvents = output => output.split('\n').filter(line => /Preloaded buffers|Loading model files|Evicted cached buffers/.test(line))
+    .map(line => line.replace('[Server] ', '').replace(' (MODEL_CACHE_BUDGET=2)', ''));
+
+test('MODEL_CACHE_BUDGET evicts the least recently used model files and reloads them on demand', async () => {
+    const server = await startServer({ pairs: ['en-ja', 'en-zh', 'zh-en'], env: { MODEL_CACHE_BUDGET: '2' } });
+    try {
+        // Only the budget's worth of models is preloaded (in parallel, so in either order)
+        assert.deepEqual(cacheEvents(server.output()).sort(), ['Preloaded buffers for en-ja', 'Preloaded buffers for en-zh']);
+
+        for (const [from, to] of [['en', 'zh'], ['zh', 'en'], ['en', 'ja'], ['en', 'zh']]) {
+            assert.equal((await server.post('/translate', { text: 'Hello', from, to })).status, 200);
+        }
+        assert.deepEqual(cacheEvents(server.output()).slice(2), [
+            // en-zh was just used, so en-ja is the least recently used
+            'Loading model files: zh-en',
+            'Evicted cached buffers for en-ja',
+            'Loading model files: en-ja',
+            'Evicted cached buffers for en-zh',
+            // The evicted en-zh is read from disk again
+            'Loading model files: en-zh',
+            'Evicted cached buffers for zh-en',
+        ]);
+    } finally {
+        await server.stop();
+    }
+});
+
+test('without a budget every model stays cached', async () => {
+    const server = await startServer({ pairs: ['en-ja', 'en-zh', 'zh-en'] });
+    try {
+        for (const [from, to] of [['en', 'zh'], ['zh', 'en'], ['en', 'ja'], ['en', 'zh']]) {
+            assert.equal((await server.post('/translate', { text: 'Hello', from, to })).status, 200);
+        }
+        assert.deepEqual(cacheEvents(server.output()).sort(), ['Preloaded buffers for en-ja', 'Preloaded buffers for en-zh', 'Preloaded buffers for zh-en']);
+    } finally {
+        await server.stop();
+    }
+});
@@ -0,0 +1,36 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import crypto from 'node:crypto';
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import { startServer } from './helpers.js';
+
+const sha256 = text => crypto.createHash('sha256').update(text).digest('hex');
+
+// Both pairs list the hash of the placeholder model file; zh-en's copy is then tampered with
+let server;
+before(async () => {
+    server = await startServer({
+        async prepare(modelsDir) {
+            for (const pair of ['en-zh', 'zh-en']) {
+                await fs.writeFile(path.join(modelsDir, pair, 'checksums.txt'), `${sha256('stub')}  model.intgemm8.bin\n`);
+            }
+            await fs.writeFile(path.join(modelsDir, 'zh-en', 'model.intgemm8.bin'), 'tampered');
+        },
+    });
+});
+after(() => server.stop());
+
+test('files matching checksums.txt load normally', async () => {
+    const res = await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' });
+    assert.equal(res.status, 200);
+    assert.equal((await res.json()).text, '[zh] Hello');
+});
+
+test('a tampered file fails the load with a checksum mismatch naming the file', async () => {
+    const res = await server.post('/translate', { text: '你好', from: 'zh', to: 'en' });
+    assert.equal(res.status, 500);
+    const body = await res.json();
+    assert.equal(body.code, 'MODEL_CHECKSUM_MISMATCH');
+    assert.match(body.error, new RegExp(`Checksum mismatch for model\\.intgemm8\\.bin in .*zh-en: expected ${sha256('stub')}, got ${sha256('tampered')}`));
+});
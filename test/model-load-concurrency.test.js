@@ -0,0 +1,87 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { execFileSync } from 'node:child_process';
+import fs from 'node:fs';
+import path from 'node:path';
+import { startServer } from './helpers.js';
+
+const PAIRS = ['en-zh', 'zh-en', 'en-ja', 'ja-en'];
+const SETTLE_MS = 500;
+// A loaded machine can take a while to spawn the server and reach the first load
+const FIRST_LOAD_TIMEOUT_MS = 10000;
+
+const sleep = ms => new Promise(resolve => setTimeout(resolve, ms));
+
+// Each model file is a FIFO, so a startup load blocks until the test writes to it. Opening the
+// write end without blocking only succeeds while the server has the read end open, which tells
+// us the load is in flight. Returns the most loads seen in flight at once.
+async function peakLoadsInFlight(env) {
+    let modelsDir = null;
+    const starting = startServer({
+        pairs: PAIRS,
+        env,
+        prepare(dir) {
+            for (const pair of PAIRS) {
+                const file = path.join(dir, pair, 'model.intgemm8.bin');
+                fs.rmSync(file);
+                execFileSync('mkfifo', [file]);
+            }
+            modelsDir = dir;
+        },
+    });
+    while (!modelsDir) await sleep(10);
+
+    const waiting = new Set(PAIRS);
+    const held = new Map();
+    let peak = 0;
+    try {
+        while (waiting.size > 0 || held.size > 0) {
+            // Collect every load that has started, then give any further ones time to show up
+            const roundStart = Date.now();
+            let lastChange = roundStart;
+            while (Date.now() - lastChange < SETTLE_MS || (held.size === 0 && Date.now() - roundStart < FIRST_LOAD_TIMEOUT_MS)) {
+                for (const pair of waiting) {
+                    try {
+                        const fd = fs.openSync(path.join(modelsDir, pair, 'model.intgemm8.bin'), fs.constants.O_WRONLY | fs.constants.O_NONBLOCK);
+                        waiting.delete(pair);
+                        held.set(pair, fd);
+                        lastChange = Date.now();
+                    } catch (err) {
+                        if (err.code !== 'ENXIO') throw err;
+                    }
+                }
+                await sleep(20);
+            }
+            peak = Math.max(peak, held.size);
+            assert.ok(held.size > 0, `no load started; still waiting for ${[...waiting].join(', ')}`);
+
+            // Let one load finish so its worker moves on to the next model
+            const [pair, fd] = held.entries().next().value;
+            fs.writeSync(fd, 'stub');
+            fs.closeSync(fd);
+            held.delete(pair);
+        }
+    } catch (err) {
+        // Don't leave the server blocked on the FIFOs: it is stopped (or times out) before we fail
+        for (const fd of held.values()) fs.closeSync(fd);
+        await starting.then(server => server.stop(), () => {});
+        throw err;
+    }
+
+    const server = await starting;
+    try {
+        for (const pair of PAIRS) assert.match(server.output(), new RegExp(`Preloaded buffers for ${pair}`));
+    } finally {
+        await server.stop();
+    }
+    return peak;
+}
+
+test('models are preloaded two at a time by default', async () => {
+    assert.equal(await peakLoadsInFlight({}), 2);
+});
+
+test('MODEL_LOAD_CONCURRENCY caps the loads in flight', async () => {
+    assert.equal(await peakLoadsInFlight({ MODEL_LOAD_CONCURRENCY: '3' }), 3);
+    assert.equal(await peakLoadsInFlight({ MODEL_LOAD_CONCURRENCY: '1' }), 1);
+});
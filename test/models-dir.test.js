@@ -0,0 +1,57 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { spawnSync } from 'node:child_process';
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+import { ROOT, STUB_GLUE } from './helpers.js';
+
+// Runs server.js to completion; a usable MODELS_DIR would keep it listening until the timeout
+function startWith(modelsDir) {
+    return spawnSync(process.execPath, [path.join(ROOT, 'server.js')], {
+        cwd: ROOT,
+        env: { PATH: process.env.PATH, IP: '127.0.0.1', PORT: '0', MODELS_DIR: modelsDir, WASM_PATH: STUB_GLUE, JS_PATH: STUB_GLUE },
+        encoding: 'utf8',
+        timeout: 10000,
+    });
+}
+
+test('an explicit MODELS_DIR that does not exist fails startup clearly', async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'linguaspark-test-'));
+    const missing = path.join(dir, 'nope');
+    try {
+        const result = startWith(missing);
+        assert.equal(result.status, 1, result.stdout + result.stderr);
+        assert.match(result.stderr, new RegExp(`MODELS_DIR '${missing.replace(/[.*+?^${}()|[\]\\]/g, '\\$&')}' does not exist or is not a directory`));
+        // The explicit path is not created on the operator's behalf
+        await assert.rejects(fs.stat(missing), { code: 'ENOENT' });
+    } finally {
+        await fs.rm(dir, { recursive: true, force: true });
+    }
+});
+
+test('a MODELS_DIR that is a file fails startup the same way', async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'linguaspark-test-'));
+    const file = path.join(dir, 'models');
+    await fs.writeFile(file, '');
+    try {
+        const result = startWith(file);
+        assert.equal(result.status, 1, result.stdout + result.stderr);
+        assert.match(result.stderr, /MODELS_DIR '.*models' does not exist or is not a directory/);
+    } finally {
+        await fs.rm(dir, { recursive: true, force: true });
+    }
+});
+
+test('an unreadable MODELS_DIR is reported as permission denied', { skip: process.getuid?.() === 0 && 'root bypasses directory permissions' }, async () => {
+    const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'linguaspark-test-'));
+    await fs.chmod(dir, 0o000);
+    try {
+        const result = startWith(dir);
+        assert.equal(result.status, 1, result.stdout + result.stderr);
+        assert.match(result.stderr, /MODELS_DIR '.*' is not readable \(permission denied\)/);
+    } finally {
+        await fs.chmod(dir, 0o700);
+        await fs.rm(dir, { recursive: true, force: true });
+    }
+});
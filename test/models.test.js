@@ -0,0 +1,17 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ pairs: ['en-zh-Hans', 'enzh-medical', 'zh-en-TW-legal'] }); });
+after(() => server.stop());
+
+test('script and region subtags are not domains', async () => {
+    const { models } = await (await server.fetch('/models')).json();
+    assert.deepEqual(models.map(m => m.key).sort(), ['en-zh', 'en-zh:medical', 'zh-en:legal']);
+});
+
+test('domain requests use the domain model', async () => {
+    const res = await server.post('/translate?debug=model', { text: 'Hello world', from: 'en', to: 'zh', domain: 'medical' });
+    assert.deepEqual((await res.json()).model_used, ['enzh-medical']);
+});
@@ -0,0 +1,64 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+// Read the stream line by line, noting when each line arrived
+async function readLines(res) {
+    const lines = [];
+    const reader = res.body.getReader();
+    const decoder = new TextDecoder();
+    let buffered = '';
+    for (let chunk = await reader.read(); !chunk.done; chunk = await reader.read()) {
+        buffered += decoder.decode(chunk.value, { stream: true });
+        let newline;
+        while ((newline = buffered.indexOf('\n')) >= 0) {
+            lines.push({ at: Date.now(), value: JSON.parse(buffered.slice(0, newline)) });
+            buffered = buffered.slice(newline + 1);
+        }
+    }
+    assert.equal(buffered, '', 'every line ends with a newline');
+    return lines;
+}
+
+test('/translate/ndjson streams one line per segment, then a summary', async () => {
+    const texts = ['Hello one', 'Hello two', 'Hello three'];
+    const res = await server.post('/translate/ndjson', { texts, from: 'en', to: 'zh' });
+    assert.equal(res.status, 200);
+    assert.match(res.headers.get('content-type'), /^application\/x-ndjson/);
+    const lines = (await readLines(res)).map(line => line.value);
+
+    // Reassemble by index, as a client would
+    const translations = [];
+    for (const line of lines.slice(0, -1)) translations[line.index] = line.text;
+    assert.deepEqual(translations, ['[zh] Hello one', '[zh] Hello two', '[zh] Hello three']);
+    assert.deepEqual(lines.at(-1), { done: true, count: 3, errors: 0, from: 'en', to: 'zh' });
+});
+
+test('lines arrive as segments complete, not all at the end', async () => {
+    // The stub spends 100 ms on each SLOW segment
+    const texts = Array.from({ length: 5 }, (_, i) => `SLOW ${i}`);
+    const res = await server.post('/translate/ndjson', { texts, from: 'en', to: 'zh' });
+    const lines = await readLines(res);
+    assert.equal(lines.length, texts.length + 1);
+    const spread = lines.at(-1).at - lines[0].at;
+    assert.ok(spread >= 250, `all lines arrived within ${spread} ms`);
+});
+
+test('the source is detected from the texts when from is omitted', async () => {
+    const res = await server.post('/translate/ndjson', { texts: ['你好', '世界'], to: 'en' });
+    const lines = (await readLines(res)).map(line => line.value);
+    assert.deepEqual(lines, [
+        { index: 0, text: '[en] 你好' },
+        { index: 1, text: '[en] 世界' },
+        { done: true, count: 2, errors: 0, from: 'zh', to: 'en' },
+    ]);
+});
+
+test('texts must be an array of strings', async () => {
+    assert.equal((await server.post('/translate/ndjson', { texts: 'Hello', to: 'zh' })).status, 400);
+    assert.equal((await server.post('/translate/ndjson', { texts: ['Hello', 1], to: 'zh' })).status, 400);
+});
@@ -0,0 +1,34 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+const sameLanguage = { text: 'Hello there', from: 'en', to: 'en' };
+
+test('a no-op translation echoes the text with 200 by default', async () => {
+    const res = await server.post('/translate', sameLanguage);
+    assert.equal(res.status, 200);
+    assert.deepEqual(await res.json(), { text: 'Hello there', from: 'en', to: 'en', skipped: true });
+});
+
+test('no_content_on_noop: true answers a no-op with 204', async () => {
+    for (const body of [sameLanguage, { text: '   ', from: 'en', to: 'zh' }]) {
+        const res = await server.post('/translate', { ...body, no_content_on_noop: true });
+        assert.equal(res.status, 204);
+        assert.equal(await res.text(), '');
+    }
+});
+
+test('Prefer: return=minimal answers a no-op with 204', async () => {
+    const res = await server.post('/translate', sameLanguage, { Prefer: 'return=minimal' });
+    assert.equal(res.status, 204);
+});
+
+test('a real translation is unaffected by the option', async () => {
+    const res = await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh', no_content_on_noop: true }, { Prefer: 'return=minimal' });
+    assert.equal(res.status, 200);
+    assert.equal((await res.json()).text, '[zh] Hello');
+});
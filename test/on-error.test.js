@@ -0,0 +1,84 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+const document = 'Hello one\n\nFAIL two\n\nHello three';
+
+async function events(res) {
+    return (await res.text()).split('\n\n').filter(Boolean).map(block => {
+        const [, event] = block.match(/^event: (.*)$/m);
+        const [, data] = block.match(/^data: (.*)$/m);
+        return [event, JSON.parse(data)];
+    });
+}
+
+async function lines(res) {
+    return (await res.text()).trim().split('\n').map(line => JSON.parse(line));
+}
+
+test('document SSE, continue: error event for the failing segment, the rest translated', async () => {
+    const res = await server.post('/translate/document', { text: document, from: 'en', to: 'zh', on_error: 'continue' }, { Accept: 'text/event-stream' });
+    assert.equal(res.status, 200);
+    assert.match(res.headers.get('content-type'), /text\/event-stream/);
+    const received = await events(res);
+    assert.deepEqual(received.slice(0, 3), [
+        ['segment', { index: 0, text: '[zh] Hello one' }],
+        ['error', { index: 1, error: 'segment rejected' }],
+        ['segment', { index: 2, text: '[zh] Hello three' }],
+    ]);
+    const [event, done] = received[3];
+    assert.equal(event, 'done');
+    assert.equal(done.text, '[zh] Hello one\n\n[untranslated] FAIL two\n\n[zh] Hello three');
+    assert.deepEqual(done.errors, [{ index: 1, error: 'segment rejected' }]);
+});
+
+test('document SSE, abort: the stream ends with an error event for the segment', async () => {
+    const res = await server.post('/translate/document', { text: document, from: 'en', to: 'zh', on_error: 'abort' }, { Accept: 'text/event-stream' });
+    assert.deepEqual(await events(res), [
+        ['segment', { index: 0, text: '[zh] Hello one' }],
+        ['error', { index: 1, error: 'segment rejected', code: 'TRANSLATION_FAILED' }],
+    ]);
+});
+
+test('document JSON, abort: the request fails', async () => {
+    const res = await server.post('/translate/document', { text: document, from: 'en', to: 'zh', on_error: 'abort' });
+    assert.equal(res.status, 500);
+});
+
+test('NDJSON, continue: error line and the remaining segments', async () => {
+    const res = await server.post('/translate/ndjson', { texts: ['Hello one', 'FAIL two', 'Hello three'], from: 'en', to: 'zh', on_error: 'continue' });
+    assert.deepEqual(await lines(res), [
+        { index: 0, text: '[zh] Hello one' },
+        { index: 1, error: 'segment rejected' },
+        { index: 2, text: '[zh] Hello three' },
+        { done: true, count: 3, errors: 1, from: 'en', to: 'zh' },
+    ]);
+});
+
+test('NDJSON aborts by default: stops after the error line', async () => {
+    const res = await server.post('/translate/ndjson', { texts: ['Hello one', 'FAIL two', 'Hello three'], from: 'en', to: 'zh' });
+    assert.deepEqual(await lines(res), [
+        { index: 0, text: '[zh] Hello one' },
+        { index: 1, error: 'segment rejected' },
+        { done: true, aborted: true, count: 2, errors: 1, from: 'en', to: 'zh' },
+    ]);
+});
+
+test('document passes a failing segment through by default, marked and reported in headers', async () => {
+    const res = await server.post('/translate/document', { text: 'Hello one\n\n  FAIL two\n\nHello three\n\nFAIL four', from: 'en', to: 'zh' });
+    assert.equal(res.status, 200);
+    assert.equal(res.headers.get('x-passthrough-count'), '2');
+    assert.equal(res.headers.get('x-passthrough-segments'), '1,3');
+    const body = await res.json();
+    assert.equal(body.text, '[zh] Hello one\n\n  [untranslated] FAIL two\n\n[zh] Hello three\n\n[untranslated] FAIL four');
+    assert.deepEqual(body.errors, [{ index: 1, error: 'segment rejected' }, { index: 3, error: 'segment rejected' }]);
+});
+
+test('passthrough_marker replaces the marker', async () => {
+    const res = await server.post('/translate/document', { text: 'Hello one\n\nFAIL two', from: 'en', to: 'zh', passthrough_marker: '' });
+    assert.equal((await res.json()).text, '[zh] Hello one\n\nFAIL two');
+});
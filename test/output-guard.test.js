@@ -0,0 +1,48 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+// The stub repeats LOOP input ten times, far past a 3x ratio
+const runaway = { text: 'LOOP of a runaway model', from: 'en', to: 'zh' };
+
+async function withGuard(action, fn) {
+    const server = await startServer({ env: { MAX_OUTPUT_RATIO: '3', ...(action && { OUTPUT_GUARD_ACTION: action }) } });
+    try {
+        await fn(server);
+    } finally {
+        await server.stop();
+    }
+}
+
+test('the guard flags runaway output by default', () => withGuard(null, async (server) => {
+    const body = await (await server.post('/translate', runaway)).json();
+    assert.equal(body.output_guard, 'flagged');
+    assert.ok(body.text.length > runaway.text.length * 3);
+    assert.match(server.output(), /Output guard triggered/);
+
+    // Output within the ratio is left alone
+    const normal = await (await server.post('/translate', { text: 'A normal sentence', from: 'en', to: 'zh' })).json();
+    assert.equal(normal.output_guard, undefined);
+}));
+
+test('OUTPUT_GUARD_ACTION=truncate cuts the output at the ratio', () => withGuard('truncate', async (server) => {
+    const body = await (await server.post('/translate', runaway)).json();
+    assert.equal(body.output_guard, 'truncated');
+    assert.equal(body.text.length, runaway.text.length * 3);
+}));
+
+test('OUTPUT_GUARD_ACTION=reject fails the request', () => withGuard('reject', async (server) => {
+    const res = await server.post('/translate', runaway);
+    assert.equal(res.status, 500);
+    assert.equal((await res.json()).code, 'OUTPUT_GUARD_REJECTED');
+}));
+
+test('no guard without MAX_OUTPUT_RATIO', async () => {
+    const server = await startServer();
+    try {
+        const body = await (await server.post('/translate', runaway)).json();
+        assert.equal(body.output_guard, undefined);
+    } finally {
+        await server.stop();
+    }
+});
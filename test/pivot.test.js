@@ -0,0 +1,59 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ pairs: ['fr-en', 'en-zh', 'zh-en'] }); });
+after(() => server.stop());
+
+test('/translate pivots when there is no direct model', async () => {
+    const res = await server.post('/translate?debug=model', { text: 'Bonjour le monde', from: 'fr', to: 'zh' });
+    assert.equal(res.status, 200);
+    assert.deepEqual(await res.json(), {
+        text: '[zh] [en] Bonjour le monde',
+        from: 'fr',
+        to: 'zh',
+        pivot_path: ['fr', 'en', 'zh'],
+        model_used: ['fr-en', 'en-zh'],
+    });
+});
+
+test('a fallback chain takes a target reachable only through a pivot', async () => {
+    const res = await server.post('/translate', { text: 'Bonjour le monde', from: 'fr', to: ['yue', 'zh'] });
+    assert.equal(res.status, 200);
+    const body = await res.json();
+    assert.equal(body.to, 'zh');
+    assert.equal(body.target_fallback, true);
+    assert.deepEqual(body.pivot_path, ['fr', 'en', 'zh']);
+});
+
+test('protected spans survive every leg', async () => {
+    const res = await server.post('/translate', { text: 'Bonjour {name}', from: 'fr', to: 'zh', protect_placeholders: true });
+    assert.equal((await res.json()).text, '[zh] [en] Bonjour {name}');
+});
+
+test('unreachable targets are still MODEL_NOT_AVAILABLE', async () => {
+    const res = await server.post('/translate', { text: 'Bonjour le monde', from: 'fr', to: ['yue', 'ko'] });
+    assert.equal(res.status, 400);
+    assert.equal((await res.json()).code, 'MODEL_NOT_AVAILABLE');
+});
+
+test('same-language requests come back unchanged without a route', async () => {
+    const res = await server.post('/translate_mtranserver?debug=model', { text: 'Hello there', from: 'en', to: 'en' });
+    assert.deepEqual(await res.json(), { result: 'Hello there' });
+    assert.doesNotMatch(server.output(), /Creating WASM instance for model: zh-en/);
+});
+
+test('MAX_PIVOT_HOPS bounds the route length', async () => {
+    const pairs = ['ko-en', 'en-zh', 'zh-ja'];
+    const body = { text: 'Annyeong', from: 'ko', to: 'ja' };
+    const oneHop = await startServer({ pairs });
+    const twoHops = await startServer({ pairs, env: { MAX_PIVOT_HOPS: '2' } });
+    try {
+        assert.equal((await oneHop.post('/translate_mtranserver', body)).status, 400);
+        const res = await twoHops.post('/translate_mtranserver', body);
+        assert.deepEqual(await res.json(), { result: '[ja] [zh] [en] Annyeong', pivot_path: ['ko', 'en', 'zh', 'ja'] });
+    } finally {
+        await Promise.all([oneHop.stop(), twoHops.stop()]);
+    }
+});
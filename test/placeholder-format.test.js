@@ -0,0 +1,50 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+const sentence = 'Visit https://a.com on day 0 and 1 now, Issue #1 costs $5 today';
+
+async function translate(server, text) {
+    const res = await server.post('/translate', { text, from: 'en', to: 'zh', preserve_entities: true, protect_placeholders: true });
+    return (await res.json()).text;
+}
+
+test('a configured format is used and restored', async () => {
+    const server = await startServer({ env: { PLACEHOLDER_FORMAT: '[[{n}]]' } });
+    try {
+        assert.equal(await translate(server, sentence), `[zh] ${sentence}`);
+        // Text that looks like a default-format placeholder is left alone under a custom format
+        assert.equal(await translate(server, 'Ask {name} about ⟦0⟧'), '[zh] Ask {name} about ⟦0⟧');
+    } finally {
+        await server.stop();
+    }
+});
+
+test('formats without text on both sides of {n} fall back to the default', async () => {
+    for (const format of ['{n}', '#{n}', '{n}# ', ' {n} ']) {
+        const server = await startServer({ env: { PLACEHOLDER_FORMAT: format } });
+        try {
+            assert.match(server.output(), /Invalid PLACEHOLDER_FORMAT=.*using "⟦\{n\}⟧"/, format);
+            assert.equal(await translate(server, sentence), `[zh] ${sentence}`, format);
+        } finally {
+            await server.stop();
+        }
+    }
+});
+
+test('a format the model drops is reported once per model', async () => {
+    // The stub model deletes <<n>> placeholders
+    const server = await startServer({ env: { PLACEHOLDER_FORMAT: '<<{n}>>' } });
+    try {
+        for (const [from, to] of [['en', 'zh'], ['zh', 'en'], ['en', 'zh'], ['zh', 'en']]) {
+            assert.equal((await server.post('/translate', { text: 'Hello world', from, to })).status, 200);
+        }
+        const warnings = server.output().match(/Placeholder format <<\{n\}>> does not survive model \S+/g);
+        assert.deepEqual(warnings, [
+            'Placeholder format <<{n}>> does not survive model en-zh:',
+            'Placeholder format <<{n}>> does not survive model zh-en:',
+        ]);
+    } finally {
+        await server.stop();
+    }
+});
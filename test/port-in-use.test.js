@@ -0,0 +1,29 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { spawnSync } from 'node:child_process';
+import { once } from 'node:events';
+import fs from 'node:fs/promises';
+import net from 'node:net';
+import path from 'node:path';
+import { ROOT, STUB_GLUE, createModelsDir } from './helpers.js';
+
+test('a taken port fails startup with a clear error', async () => {
+    const blocker = net.createServer();
+    blocker.listen(0, '127.0.0.1');
+    await once(blocker, 'listening');
+    const { port } = blocker.address();
+    const modelsDir = await createModelsDir(['en-zh']);
+    try {
+        const result = spawnSync(process.execPath, [path.join(ROOT, 'server.js')], {
+            cwd: ROOT,
+            env: { PATH: process.env.PATH, IP: '127.0.0.1', PORT: String(port), MODELS_DIR: modelsDir, WASM_PATH: STUB_GLUE, JS_PATH: STUB_GLUE },
+            encoding: 'utf8',
+            timeout: 10000,
+        });
+        assert.equal(result.status, 1, result.stdout + result.stderr);
+        assert.match(result.stderr, new RegExp(`\\[Server\\] Port ${port} on 127\\.0\\.0\\.1 is already in use`));
+    } finally {
+        blocker.close();
+        await fs.rm(modelsDir, { recursive: true, force: true });
+    }
+});
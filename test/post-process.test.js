@@ -0,0 +1,39 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+const hello = { text: 'Hello world', from: 'en', to: 'zh' };
+
+async function translateWith(env) {
+    const server = await startServer({ env });
+    try {
+        const res = await server.post('/translate', hello);
+        assert.equal(res.status, 200);
+        return { text: (await res.json()).text, output: server.output() };
+    } finally {
+        await server.stop();
+    }
+}
+
+test('POST_PROCESS_CMD transforms the translation', async () => {
+    const { text } = await translateWith({ POST_PROCESS_CMD: 'tr a-z A-Z' });
+    assert.equal(text, '[ZH] HELLO WORLD');
+});
+
+test('a failing command falls back to the unprocessed translation', async () => {
+    const { text, output } = await translateWith({ POST_PROCESS_CMD: 'echo broken >&2; exit 3' });
+    assert.equal(text, '[zh] Hello world');
+    assert.match(output, /Post-processor failed \(exit 3: broken\), returning unprocessed translation/);
+});
+
+test('a command over POST_PROCESS_TIMEOUT_MS is killed and skipped', async () => {
+    const started = Date.now();
+    const { text, output } = await translateWith({ POST_PROCESS_CMD: 'sleep 5', POST_PROCESS_TIMEOUT_MS: '200' });
+    assert.equal(text, '[zh] Hello world');
+    assert.match(output, /Post-processor failed \(timed out after 200ms\)/);
+    assert.ok(Date.now() - started < 4000);
+});
+
+test('without POST_PROCESS_CMD the translation is returned as is', async () => {
+    assert.equal((await translateWith({})).text, '[zh] Hello world');
+});
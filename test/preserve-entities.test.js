@@ -0,0 +1,19 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+const text = 'Pay $1,000 at https://shop.example.com/pay?id=1,234 or mail billing@example.com.';
+
+test('preserve_entities keeps URLs, emails and numbers byte-identical', async () => {
+    const res = await server.post('/translate', { text, from: 'en', to: 'zh', preserve_entities: true });
+    assert.equal((await res.json()).text, `[zh] ${text}`);
+});
+
+test('without preserve_entities the model reformats them', async () => {
+    const res = await server.post('/translate', { text, from: 'en', to: 'zh' });
+    assert.equal((await res.json()).text, '[zh] Pay $1000 at https://shop.example.com/pay?id=1234 or mail billing@example.com.');
+});
@@ -0,0 +1,27 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ pairs: ['ja-en', 'en-zh'] }); });
+after(() => server.stop());
+
+// The stub reads "Close" as the verb "Close(v)" when it sees "button", so it shows whether the
+// model was given the embedded English
+const text = '今すぐ Close button を押して iPhone 15 を買おう';
+
+test('protect_foreign_script keeps Latin runs in a Japanese sentence verbatim', async () => {
+    const res = await server.post('/translate', { text, from: 'ja', to: 'en', protect_foreign_script: true });
+    assert.equal((await res.json()).text, '[en] 今すぐ Close button を押して iPhone 15 を買おう');
+});
+
+test('without protect_foreign_script the embedded English goes to the model', async () => {
+    const res = await server.post('/translate', { text, from: 'ja', to: 'en' });
+    assert.equal((await res.json()).text, '[en] 今すぐ Close(v) button を押して iPhone 15 を買おう');
+});
+
+test('runs in the source script are still translated', async () => {
+    // Latin is the source script for English, so nothing here is foreign
+    const res = await server.post('/translate', { text: 'Press the Close button', from: 'en', to: 'zh', protect_foreign_script: true });
+    assert.equal((await res.json()).text, '[zh] Press the Close(v) button');
+});
@@ -0,0 +1,38 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+const text = 'Hello {0}, you have {1} messages from %s in {{folder}}';
+const translate = (body, target = server) => target.post('/translate', { text, from: 'en', to: 'zh', ...body });
+
+test('protect_placeholders keeps every placeholder intact', async () => {
+    const body = await (await translate({ protect_placeholders: true })).json();
+    assert.equal(body.text, `[zh] ${text}`);
+    assert.equal(body.placeholder_mismatches, undefined);
+});
+
+test('without protection the model mangles them', async () => {
+    const body = await (await translate({})).json();
+    assert.equal(body.text, '[zh] Hello { 0 }, you have { 1 } messages from %s in {{folder}}');
+});
+
+test('a placeholder the model drops is reported and logged', async () => {
+    // The stub drops <<n>>, so every protected placeholder is lost
+    const lossy = await startServer({ env: { PLACEHOLDER_FORMAT: '<<{n}>>' } });
+    try {
+        const body = await (await translate({ protect_placeholders: true }, lossy)).json();
+        assert.deepEqual(body.placeholder_mismatches, [
+            { placeholder: '{0}', expected: 1, found: 0 },
+            { placeholder: '{1}', expected: 1, found: 0 },
+            { placeholder: '%s', expected: 1, found: 0 },
+            { placeholder: '{{folder}}', expected: 1, found: 0 },
+        ]);
+        assert.match(lossy.output(), /Placeholders lost or duplicated in en-zh translation: \{0\}, \{1\}, %s, \{\{folder\}\}/);
+    } finally {
+        await lossy.stop();
+    }
+});
@@ -0,0 +1,81 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+// Length-delimited string fields only; every field used here is below 16, so tags are one byte
+function encodeStrings(fields) {
+    const bytes = [];
+    for (const [number, value] of fields) {
+        const data = Buffer.from(value, 'utf8');
+        bytes.push(number * 8 + 2, data.length, ...data);
+    }
+    return Buffer.from(bytes);
+}
+
+function decodeStrings(buffer) {
+    const fields = {};
+    for (let pos = 0; pos < buffer.length;) {
+        const tag = buffer[pos++];
+        if (tag % 8 === 0) {
+            fields[tag >> 3] = buffer[pos++];
+            continue;
+        }
+        const length = buffer[pos++];
+        fields[tag >> 3] = buffer.toString('utf8', pos, pos + length);
+        pos += length;
+    }
+    return fields;
+}
+
+test('protobuf request gets a protobuf response', async () => {
+    const res = await server.fetch('/translate', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/x-protobuf' },
+        body: encodeStrings([[1, 'Hello world'], [2, 'en'], [3, 'zh']]),
+    });
+    assert.equal(res.status, 200);
+    assert.match(res.headers.get('content-type'), /application\/x-protobuf/);
+    const fields = decodeStrings(Buffer.from(await res.arrayBuffer()));
+    assert.deepEqual(fields, { 1: '[zh] Hello world', 2: 'en', 3: 'zh' });
+});
+
+test('protobuf request can ask for JSON', async () => {
+    const res = await server.fetch('/translate', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/x-protobuf', Accept: 'application/json' },
+        body: encodeStrings([[1, 'Hello world'], [2, 'en'], [3, 'zh']]),
+    });
+    assert.deepEqual(await res.json(), { text: '[zh] Hello world', from: 'en', to: 'zh' });
+});
+
+test('truncated protobuf is a 400', async () => {
+    const res = await server.fetch('/translate', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/x-protobuf' },
+        body: Buffer.from([0x0a, 0x10, 0x41]),
+    });
+    assert.equal(res.status, 400);
+});
+
+test('a known field with the wrong wire type is a 400', async () => {
+    // text (field 1) sent as a varint instead of a string
+    const res = await server.fetch('/translate', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/x-protobuf' },
+        body: Buffer.concat([Buffer.from([0x08, 0x7b]), encodeStrings([[2, 'en'], [3, 'zh']])]),
+    });
+    assert.equal(res.status, 400);
+    assert.match((await res.json()).error, /field 1 \(text\) has wire type 0/);
+});
+
+test('non-string JSON text is a 400', async () => {
+    for (const text of [123, ['Hello'], { value: 'Hello' }]) {
+        const res = await server.post('/translate', { text, from: 'en', to: 'zh' });
+        assert.equal(res.status, 400, JSON.stringify(text));
+        assert.equal((await res.json()).error, 'text must be a string');
+    }
+});
@@ -0,0 +1,41 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+async function modelUsed(server, extra = {}) {
+    const res = await server.post('/translate?debug=model', { text: 'Hello world', from: 'en', to: 'zh', ...extra });
+    assert.equal(res.status, 200);
+    return (await res.json()).model_used;
+}
+
+test('quality: high uses the -hq variant and the default uses -fast', async () => {
+    const server = await startServer({ pairs: ['enzh-fast', 'enzh-hq'] });
+    try {
+        assert.deepEqual(await modelUsed(server), ['enzh-fast']);
+        assert.deepEqual(await modelUsed(server, { quality: 'high' }), ['enzh-hq']);
+        assert.deepEqual(await modelUsed(server, { quality: 'fast' }), ['enzh-fast']);
+    } finally {
+        await server.stop();
+    }
+});
+
+test('a pair with a single variant serves every quality', async () => {
+    const server = await startServer({ pairs: ['enzh-hq'] });
+    try {
+        assert.deepEqual(await modelUsed(server), ['enzh-hq']);
+        assert.deepEqual(await modelUsed(server, { quality: 'high' }), ['enzh-hq']);
+    } finally {
+        await server.stop();
+    }
+});
+
+test('an unknown quality is a 400', async () => {
+    const server = await startServer();
+    try {
+        const res = await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh', quality: 'best' });
+        assert.equal(res.status, 400);
+        assert.equal((await res.json()).error, 'Invalid quality: expected one of fast, high');
+    } finally {
+        await server.stop();
+    }
+});
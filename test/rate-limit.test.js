@@ -0,0 +1,43 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ env: { PAIR_RATE_LIMITS: 'enzh:2' } }); });
+after(() => server.stop());
+
+const body = { text: 'Hello world', from: 'en', to: 'zh' };
+
+test('warmup is not charged', async () => {
+    for (let i = 0; i < 3; i++) {
+        assert.equal((await server.post('/translate', { from: 'en', to: 'zh', warmup: true })).status, 200);
+    }
+});
+
+test('same-language requests are not charged to any pair', async () => {
+    for (let i = 0; i < 3; i++) {
+        const single = await server.post('/translate_mtranserver', { text: 'Hello there', from: 'en', to: 'en' });
+        assert.deepEqual(await single.json(), { result: 'Hello there' });
+        const batch = await server.post('/translate_mtranserver/batch', { texts: ['你好', '世界'], from: 'zh', to: 'zh' });
+        assert.deepEqual(await batch.json(), { results: ['你好', '世界'] });
+    }
+});
+
+test('a multi-segment request is charged once', async () => {
+    const res = await server.post('/translate/document', { text: 'One\n\nTwo\n\nThree', from: 'en', to: 'zh' });
+    assert.equal(res.status, 200);
+    assert.equal((await server.post('/translate', body)).status, 200);
+});
+
+test('requests over PAIR_RATE_LIMITS get 429 with Retry-After', async () => {
+    const limited = await server.post('/translate', body);
+    assert.equal(limited.status, 429);
+    assert.equal((await limited.json()).code, 'RATE_LIMITED');
+    const retryAfter = Number(limited.headers.get('retry-after'));
+    assert.ok(retryAfter >= 1 && retryAfter <= 60, `Retry-After ${retryAfter}`);
+});
+
+test('other pairs are not limited', async () => {
+    const res = await server.post('/translate', { text: '你好世界', from: 'zh', to: 'en' });
+    assert.equal(res.status, 200);
+});
@@ -0,0 +1,36 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ env: { REQUEST_LOG_SIZE: '3' } }); });
+after(() => server.stop());
+
+const recent = async () => (await (await server.fetch('/debug/requests')).json()).requests;
+
+test('/debug/requests lists the last requests most recent first, without text', async () => {
+    await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' });
+    await server.post('/translate', { text: '你好世界', from: 'zh', to: 'en' });
+    await server.post('/translate', { from: 'en', to: 'zh' });
+
+    const requests = await recent();
+    assert.deepEqual(requests.map(({ method, path, from, to, chars, status }) => ({ method, path, from, to, chars, status })), [
+        { method: 'POST', path: '/translate', from: 'en', to: 'zh', chars: 0, status: 400 },
+        { method: 'POST', path: '/translate', from: 'zh', to: 'en', chars: 4, status: 200 },
+        { method: 'POST', path: '/translate', from: 'en', to: 'zh', chars: 5, status: 200 },
+    ]);
+    for (const entry of requests) {
+        assert.equal(typeof entry.timestamp, 'number');
+        assert.ok(entry.latencyMs >= 0);
+        assert.ok(!('text' in entry));
+    }
+    assert.ok(requests[0].timestamp >= requests[2].timestamp);
+});
+
+test('the log keeps only REQUEST_LOG_SIZE entries', async () => {
+    // The previous /debug/requests call is now the newest entry and the first translation is gone
+    const requests = await recent();
+    assert.equal(requests.length, 3);
+    assert.equal(requests[0].path, '/debug/requests');
+    assert.equal(requests[2].from, 'zh');
+});
@@ -0,0 +1,47 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import { startServer } from './helpers.js';
+
+// One contract for every transient failure: a Retry-After header in whole seconds.
+// en-zh is required and starts without its model file, so /health stays warming.
+let server;
+before(async () => {
+    server = await startServer({
+        pairs: ['en-zh', 'zh-en', 'en-ja'],
+        env: { PAIR_RATE_LIMITS: 'zhen:1', REQUIRED_PAIRS: 'enzh' },
+        prepare: modelsDir => fs.rm(path.join(modelsDir, 'en-zh', 'model.intgemm8.bin')),
+    });
+});
+after(() => server.stop());
+
+test('429 RATE_LIMITED: until the oldest request leaves the one-minute window', async () => {
+    const body = { text: '你好', from: 'zh', to: 'en' };
+    assert.equal((await server.post('/translate', body)).status, 200);
+    const res = await server.post('/translate', body);
+    assert.equal(res.status, 429);
+    const retryAfter = Number(res.headers.get('retry-after'));
+    assert.ok(retryAfter >= 58 && retryAfter <= 60, `Retry-After ${retryAfter}`);
+});
+
+test('502 TRANSLATOR_UNAVAILABLE (engine crashed, reloading): 1 second', async () => {
+    const res = await server.post('/translate', { text: 'CRASH', from: 'en', to: 'ja' });
+    assert.equal(res.status, 502);
+    assert.equal(res.headers.get('retry-after'), '1');
+});
+
+test('503 from /health while warming: 5 seconds', async () => {
+    const res = await server.fetch('/health');
+    assert.equal(res.status, 503);
+    assert.equal(res.headers.get('retry-after'), '5');
+});
+
+test('errors a plain retry will not fix carry no Retry-After', async () => {
+    const badRequest = await server.post('/translate', { from: 'en', to: 'zh' });
+    assert.equal(badRequest.status, 400);
+    assert.equal(badRequest.headers.get('retry-after'), null);
+    const unsupported = await server.post('/translate', { text: 'Hallo', from: 'de', to: 'fr' });
+    assert.equal(unsupported.status, 400);
+    assert.equal(unsupported.headers.get('retry-after'), null);
+});
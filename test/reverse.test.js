@@ -0,0 +1,26 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ pairs: ['en-zh', 'zh-en', 'en-ja'] }); });
+after(() => server.stop());
+
+test('reverse: true translates back from to into from', async () => {
+    const res = await server.post('/translate', { text: '你好', from: 'en', to: 'zh', reverse: true });
+    assert.equal(res.status, 200);
+    assert.deepEqual(await res.json(), { text: '[en] 你好', from: 'zh', to: 'en' });
+});
+
+test('reverse needs an explicit from and a single to', async () => {
+    for (const body of [{ to: 'zh' }, { from: 'auto', to: 'zh' }, { from: 'en', to: ['zh', 'ja'] }]) {
+        const res = await server.post('/translate', { text: '你好', reverse: true, ...body });
+        assert.equal(res.status, 400, JSON.stringify(body));
+    }
+});
+
+test('reverse fails when the reverse pair has no model', async () => {
+    const res = await server.post('/translate', { text: 'こんにちは', from: 'en', to: 'ja', reverse: true });
+    assert.equal(res.status, 400);
+    assert.equal((await res.json()).error, "Translation from 'ja' to 'en' is not supported");
+});
@@ -0,0 +1,32 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ pairs: ['en-zh', 'en-ja', 'en-ko', 'en-ru', 'zh-en'] }); });
+after(() => server.stop());
+
+async function romanize(text, to, from = 'en') {
+    const res = await server.post('/translate', { text, from, to, romanize: true });
+    assert.equal(res.status, 200);
+    return (await res.json()).romanized;
+}
+
+test('Chinese output gets pinyin with tone marks', async () => {
+    assert.match(await romanize('你好世界', 'zh'), /nǐ hǎo shì jiè/);
+});
+
+test('Japanese, Korean and Russian output are transliterated', async () => {
+    assert.equal(await romanize('こんにちは、トーキョー', 'ja'), '[ja] konnichiha、tookyoo');
+    assert.equal(await romanize('안녕하세요', 'ko'), '[ko] annyeonghaseyo');
+    assert.equal(await romanize('Привет, мир', 'ru'), '[ru] Privet, mir');
+});
+
+test('Latin-script output has no romanized field', async () => {
+    assert.equal(await romanize('你好', 'en', 'zh'), undefined);
+});
+
+test('romanized is only added on request', async () => {
+    const res = await server.post('/translate', { text: '你好', from: 'en', to: 'zh' });
+    assert.equal((await res.json()).romanized, undefined);
+});
@@ -0,0 +1,41 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+test('/translate/segment previews the document split without translating', async () => {
+    const text = 'Intro. It has two sentences.\n\nSecond paragraph.\r\n\r\n\nThird one! Is it? Yes.';
+    const res = await server.post('/translate/segment', { text });
+    assert.equal(res.status, 200);
+    const body = await res.json();
+    assert.deepEqual(body.segments.map(({ text, separator, sentences }) => ({ text, separator, sentences })), [
+        { text: 'Intro. It has two sentences.', separator: '\n\n', sentences: 2 },
+        { text: 'Second paragraph.', separator: '\r\n\r\n\n', sentences: 1 },
+        { text: 'Third one! Is it? Yes.', separator: '', sentences: 3 },
+    ]);
+    assert.deepEqual(body.segments.map(s => s.index), [0, 1, 2]);
+    assert.deepEqual(body.segments.map(s => s.chars), [28, 17, 22]);
+    assert.equal(body.count, 3);
+    assert.equal(body.translation_calls, 3);
+    assert.equal(body.total_chars, text.length);
+    // The split matches what /translate/document would do with the same text
+    const document = await (await server.post('/translate/document', { text, from: 'en', to: 'zh', segmented: true })).json();
+    assert.deepEqual(document.segments.map(s => s.source), body.segments.map(s => s.text));
+});
+
+test('/translate/segment never touches the model', async () => {
+    const fresh = await startServer();
+    try {
+        assert.equal((await fresh.post('/translate/segment', { text: 'One.\n\nTwo.' })).status, 200);
+        assert.doesNotMatch(fresh.output(), /Creating WASM instance/);
+    } finally {
+        await fresh.stop();
+    }
+});
+
+test('/translate/segment needs text', async () => {
+    assert.equal((await server.post('/translate/segment', {})).status, 400);
+});
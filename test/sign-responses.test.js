@@ -0,0 +1,46 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import crypto from 'node:crypto';
+import { startServer } from './helpers.js';
+
+const KEY = 'hmac-secret';
+
+// What a client does: recompute the HMAC over "from\nto\ntext" and compare
+function verifies(signature, { text, from, to }, key) {
+    const expected = 'sha256=' + crypto.createHmac('sha256', key).update(`${from}\n${to}\n${text}`).digest('hex');
+    return signature === expected;
+}
+
+let server;
+before(async () => { server = await startServer({ env: { SIGN_RESPONSES: '1', RESPONSE_HMAC_KEY: KEY } }); });
+after(() => server.stop());
+
+test('/translate responses carry an X-Signature that verifies with the key', async () => {
+    const res = await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' });
+    const signature = res.headers.get('x-signature');
+    const body = await res.json();
+    assert.match(signature, /^sha256=[0-9a-f]{64}$/);
+    assert.ok(verifies(signature, body, KEY));
+    assert.ok(!verifies(signature, body, 'wrong-key'));
+    // A tampered body no longer matches
+    assert.ok(!verifies(signature, { ...body, text: '[zh] Goodbye' }, KEY));
+});
+
+test('/translate/document responses are signed too', async () => {
+    const res = await server.post('/translate/document', { text: 'One\n\nTwo', from: 'en', to: 'zh' });
+    assert.ok(verifies(res.headers.get('x-signature'), await res.json(), KEY));
+});
+
+test('without SIGN_RESPONSES there is no signature', async () => {
+    const plain = await startServer();
+    try {
+        const res = await plain.post('/translate', { text: 'Hello', from: 'en', to: 'zh' });
+        assert.equal(res.headers.get('x-signature'), null);
+    } finally {
+        await plain.stop();
+    }
+});
+
+test('SIGN_RESPONSES without RESPONSE_HMAC_KEY fails startup', async () => {
+    await assert.rejects(startServer({ env: { SIGN_RESPONSES: '1' } }), /SIGN_RESPONSES requires RESPONSE_HMAC_KEY/);
+});
@@ -0,0 +1,33 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+const mislabeled = { text: '今天天气很好，我们去公园散步吧。', from: 'fr', to: 'zh' };
+
+// The threshold is kept low so the test doesn't hinge on the detector's exact scores
+const env = { SKIP_WHEN_DETECTED_TARGET: '1', SKIP_DETECTED_TARGET_CONFIDENCE: '0.3' };
+
+test('text detected as the target language is echoed despite its from', async () => {
+    const server = await startServer({ pairs: ['fr-zh', 'en-zh'], env });
+    try {
+        const res = await server.post('/translate', mislabeled);
+        assert.deepEqual(await res.json(), { ...mislabeled, skipped: true });
+        assert.doesNotMatch(server.output(), /Creating WASM instance/);
+
+        // Text in another language is still translated
+        const english = await server.post('/translate', { text: 'The weather is nice today.', from: 'en', to: 'zh' });
+        assert.equal((await english.json()).text, '[zh] The weather is nice today.');
+    } finally {
+        await server.stop();
+    }
+});
+
+test('without SKIP_WHEN_DETECTED_TARGET the labeled pair is used', async () => {
+    const server = await startServer({ pairs: ['fr-zh'] });
+    try {
+        const res = await server.post('/translate', mislabeled);
+        assert.equal((await res.json()).text, `[zh] ${mislabeled.text}`);
+    } finally {
+        await server.stop();
+    }
+});
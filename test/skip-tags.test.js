@@ -0,0 +1,33 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+// The stub mangles numbers with thousands separators (1,000 -> 1000), which shows what the model saw
+const text = 'Run <code>npm i 1,000</code> then <kbd>Ctrl+C</kbd> after 2,000 ms';
+
+test('skip_tags leaves the listed elements verbatim while the prose is translated', async () => {
+    const res = await server.post('/translate', { text, from: 'en', to: 'zh', skip_tags: ['code', 'kbd'] });
+    assert.equal((await res.json()).text, '[zh] Run <code>npm i 1,000</code> then <kbd>Ctrl+C</kbd> after 2000 ms');
+});
+
+test('elements not listed in skip_tags are translated', async () => {
+    const res = await server.post('/translate', { text, from: 'en', to: 'zh', skip_tags: ['kbd'] });
+    assert.equal((await res.json()).text, '[zh] Run <code>npm i 1000</code> then <kbd>Ctrl+C</kbd> after 2000 ms');
+});
+
+test('skip_tags applies to every paragraph of a document', async () => {
+    const res = await server.post('/translate/document', {
+        text: 'Type <pre>1,000</pre>\n\nNot 1,000', from: 'en', to: 'zh', skip_tags: ['pre'],
+    });
+    assert.equal((await res.json()).text, '[zh] Type <pre>1,000</pre>\n\n[zh] Not 1000');
+});
+
+test('skip_tags must be a list of tag names', async () => {
+    const res = await server.post('/translate', { text, from: 'en', to: 'zh', skip_tags: 'code' });
+    assert.equal(res.status, 400);
+    assert.match((await res.json()).error, /skip_tags must be an array of tag names/);
+});
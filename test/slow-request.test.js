@@ -0,0 +1,30 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => {
+    server = await startServer({ env: { SLOW_REQUEST_MS: '1' } });
+});
+after(() => server.stop());
+
+// Long enough that segmenting or detecting it takes well over a millisecond
+const text = 'The quick brown fox jumps over the lazy dog. '.repeat(2000);
+
+// The warning is logged when the response finishes, which can trail the client seeing it
+const settle = () => new Promise(resolve => setTimeout(resolve, 100));
+
+test('SLOW_REQUEST_MS does not report requests that never translate', async () => {
+    assert.equal((await server.post('/detect', { text })).status, 200);
+    assert.equal((await server.post('/translate/segment', { text })).status, 200);
+    await settle();
+    assert.doesNotMatch(server.output(), /Slow request/);
+});
+
+test('SLOW_REQUEST_MS reports slow translations without their text', async () => {
+    const sentence = text.slice(0, 4500);
+    assert.equal((await server.post('/translate', { text: sentence, from: 'en', to: 'zh' })).status, 200);
+    await settle();
+    assert.match(server.output(), /\[Server\] Slow request: POST \/translate en-zh 4500 chars took \d+ms/);
+    assert.doesNotMatch(server.output(), /Slow request.*quick brown fox/);
+});
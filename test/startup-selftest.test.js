@@ -0,0 +1,39 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import { startServer } from './helpers.js';
+
+// The stub's zh-en model returns empty output for everything, like a broken model file
+const brokenZhEn = modelsDir => fs.writeFile(path.join(modelsDir, 'zh-en', 'bergamot-config.yml'), 'stub-blank: true\n');
+
+test('STARTUP_SELFTEST flags a pair whose model returns empty output', async () => {
+    const server = await startServer({ env: { STARTUP_SELFTEST: '1' }, prepare: brokenZhEn });
+    try {
+        assert.match(server.output(), /Self-test passed for en-zh/);
+        assert.match(server.output(), /Self-test failed for zh-en: empty output/);
+        const health = await (await server.fetch('/health')).json();
+        assert.deepEqual(health.unhealthyPairs, [{ key: 'zh-en', error: 'Startup self-test failed: empty output' }]);
+        // The healthy pair still serves
+        assert.equal((await (await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' })).json()).text, '[zh] Hello');
+    } finally {
+        await server.stop();
+    }
+});
+
+test('a failing REQUIRED_PAIRS entry aborts startup', async () => {
+    await assert.rejects(
+        startServer({ env: { STARTUP_SELFTEST: '1', REQUIRED_PAIRS: 'zhen' }, prepare: brokenZhEn }),
+        /Startup self-test failed for required pair\(s\): zh-en/,
+    );
+});
+
+test('without STARTUP_SELFTEST no pair is tested', async () => {
+    const server = await startServer({ prepare: brokenZhEn });
+    try {
+        assert.doesNotMatch(server.output(), /Self-test/);
+        assert.equal((await (await server.fetch('/health')).json()).unhealthyPairs, undefined);
+    } finally {
+        await server.stop();
+    }
+});
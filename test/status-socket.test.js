@@ -0,0 +1,70 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import crypto from 'node:crypto';
+import net from 'node:net';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+// Masked client frame, as browsers send them (payloads here are under 126 bytes)
+function clientFrame(opcode, payload = '') {
+    const data = Buffer.from(payload);
+    const mask = crypto.randomBytes(4);
+    return Buffer.concat([Buffer.from([0x80 | opcode, 0x80 | data.length]), mask, data.map((byte, i) => byte ^ mask[i % 4])]);
+}
+
+// Server frames are unmasked and, apart from status snapshots, short
+function* serverFrames(buffer) {
+    let offset = 0;
+    while (offset + 2 <= buffer.length) {
+        let length = buffer[offset + 1];
+        let start = offset + 2;
+        if (length === 126) {
+            length = buffer.readUInt16BE(offset + 2);
+            start += 2;
+        }
+        if (start + length > buffer.length) return;
+        yield { opcode: buffer[offset] & 0x0f, payload: buffer.toString('utf8', start, start + length) };
+        offset = start + length;
+    }
+}
+
+// Open /ws/status, run `send(socket)` after the handshake, and collect the frames received
+// until the server closes the connection
+async function exchange(send) {
+    const socket = net.connect(server.port, '127.0.0.1');
+    const chunks = [];
+    socket.on('data', chunk => chunks.push(chunk));
+    socket.write('GET /ws/status HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n'
+        + `Sec-WebSocket-Key: ${crypto.randomBytes(16).toString('base64')}\r\nSec-WebSocket-Version: 13\r\n\r\n`);
+    await new Promise(resolve => socket.once('data', resolve));
+    await send(socket);
+    await new Promise((resolve, reject) => {
+        socket.on('close', resolve);
+        setTimeout(() => reject(new Error('server did not close the socket')), 3000).unref();
+    });
+    const response = Buffer.concat(chunks);
+    const body = response.subarray(response.indexOf('\r\n\r\n') + 4);
+    return [...serverFrames(body)].filter(frame => frame.opcode !== 0x1);
+}
+
+test('a frame split across writes is answered once complete', async () => {
+    const frames = await exchange(async (socket) => {
+        const ping = clientFrame(0x9, 'split ping');
+        socket.write(ping.subarray(0, 3));
+        await new Promise(resolve => setTimeout(resolve, 50));
+        socket.write(ping.subarray(3));
+        await new Promise(resolve => setTimeout(resolve, 50));
+        socket.write(clientFrame(0x8));
+    });
+    assert.deepEqual(frames, [{ opcode: 0xa, payload: 'split ping' }, { opcode: 0x8, payload: '' }]);
+});
+
+test('frames coalesced into one write are each answered', async () => {
+    const frames = await exchange((socket) => {
+        socket.write(Buffer.concat([clientFrame(0x9, 'one'), clientFrame(0x9, 'two'), clientFrame(0x8)]));
+    });
+    assert.deepEqual(frames, [{ opcode: 0xa, payload: 'one' }, { opcode: 0xa, payload: 'two' }, { opcode: 0x8, payload: '' }]);
+});
@@ -0,0 +1,47 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => {
+    server = await startServer();
+});
+after(() => server.stop());
+
+async function listedOptions() {
+    const res = await server.fetch('/translate/options');
+    assert.equal(res.status, 200);
+    return new Map((await res.json()).options.map(option => [option.name, option]));
+}
+
+test('GET /translate/options lists each option with its type and default', async () => {
+    const options = await listedOptions();
+    assert.deepEqual(options.get('quality'), {
+        name: 'quality', type: 'string', values: ['fast', 'high'], default: 'fast', description: 'Model quality tier',
+    });
+    assert.equal(options.get('protect_glossary').type, 'boolean');
+    assert.equal(options.get('protect_glossary').default, true);
+    assert.equal(options.get('text').required, true);
+    // The handler's checks stay on the server
+    for (const option of options.values()) {
+        assert.ok(!('valid' in option) && !('invalid' in option), option.name);
+    }
+});
+
+test('POST /translate validates against the listed values', async () => {
+    const options = await listedOptions();
+    for (const name of ['quality', 'whitespace']) {
+        const res = await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh', [name]: 'bogus' });
+        assert.equal(res.status, 400);
+        assert.equal((await res.json()).error, `Invalid ${name}: expected one of ${options.get(name).values.join(', ')}`);
+    }
+    const res = await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh', context: 42 });
+    assert.equal(res.status, 400);
+    assert.equal((await res.json()).error, 'context must be a string');
+});
+
+test('POST /translate applies the listed defaults', async () => {
+    const res = await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' });
+    assert.equal(res.status, 200);
+    assert.deepEqual(await res.json(), { text: '[zh] Hello', from: 'en', to: 'zh' });
+});
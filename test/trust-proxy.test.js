@@ -0,0 +1,25 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+const forwarded = { 'X-Forwarded-For': '203.0.113.7, 10.0.0.2' };
+
+// The client IP recorded for a translation sent through a proxy that set X-Forwarded-For
+async function loggedIp(env) {
+    const server = await startServer({ env });
+    try {
+        await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' }, forwarded);
+        const { requests } = await (await server.fetch('/debug/requests')).json();
+        return requests.find(entry => entry.path === '/translate').ip;
+    } finally {
+        await server.stop();
+    }
+}
+
+test('TRUST_PROXY_HEADERS=1 records the leftmost X-Forwarded-For entry', async () => {
+    assert.equal(await loggedIp({ TRUST_PROXY_HEADERS: '1' }), '203.0.113.7');
+});
+
+test('without TRUST_PROXY_HEADERS the socket address is recorded', async () => {
+    assert.match(await loggedIp({}), /^(::ffff:)?127\.0\.0\.1$/);
+});
@@ -0,0 +1,25 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+test('the web UI is off by default', async () => {
+    const server = await startServer();
+    try {
+        assert.equal((await server.fetch('/')).status, 404);
+        assert.equal((await server.fetch('/monitor.html')).status, 404);
+        assert.equal((await server.fetch('/health')).status, 200);
+    } finally {
+        await server.stop();
+    }
+});
+
+test('SERVE_UI=1 serves it', async () => {
+    const server = await startServer({ env: { SERVE_UI: '1' } });
+    try {
+        const res = await server.fetch('/');
+        assert.equal(res.status, 200);
+        assert.match(res.headers.get('content-type'), /text\/html/);
+    } finally {
+        await server.stop();
+    }
+});
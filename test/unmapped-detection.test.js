@@ -0,0 +1,32 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+// franc detects Khmer ('khm'), which has no ISO 639-1 mapping in the server's table
+const khmer = 'សួស្តីពិភពលោក សូមស្វាគមន៍';
+
+test('an unmapped detection code falls back to English with a single warning', async () => {
+    const server = await startServer();
+    try {
+        assert.deepEqual(await (await server.post('/detect', { text: khmer })).json(), { language: 'en' });
+        const res = await server.post('/translate', { text: khmer, to: 'zh' });
+        assert.equal(res.status, 200);
+        assert.deepEqual(await res.json(), { text: `[zh] ${khmer}`, from: 'en', to: 'zh' });
+        await server.post('/detect', { text: `${khmer} ${khmer}` });
+        const warnings = server.output().match(/Detected language code 'khm' has no ISO 639-1 mapping, using en/g);
+        assert.equal(warnings?.length, 1);
+    } finally {
+        await server.stop();
+    }
+});
+
+test('the fallback is DEFAULT_SOURCE_LANG when set', async () => {
+    const server = await startServer({ env: { DEFAULT_SOURCE_LANG: 'zh' } });
+    try {
+        const res = await server.post('/translate', { text: khmer, to: 'en' });
+        assert.deepEqual(await res.json(), { text: `[en] ${khmer}`, from: 'zh', to: 'en' });
+        assert.match(server.output(), /Detected language code 'khm' has no ISO 639-1 mapping, using zh/);
+    } finally {
+        await server.stop();
+    }
+});
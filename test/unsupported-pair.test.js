@@ -0,0 +1,24 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer({ pairs: ['en-zh', 'zh-en', 'en-ja'] }); });
+after(() => server.stop());
+
+test('an unsupported pair lists the available source and target languages', async () => {
+    const res = await server.post('/translate', { text: '안녕하세요', from: 'ko', to: 'zh' });
+    assert.equal(res.status, 400);
+    assert.deepEqual(await res.json(), {
+        error: "Translation from 'ko' to 'zh' is not supported",
+        code: 'MODEL_NOT_AVAILABLE',
+        available_sources: ['en', 'zh'],
+        available_targets: ['en', 'ja', 'zh'],
+    });
+});
+
+test('a domain request for an unsupported pair names the plain languages', async () => {
+    const res = await server.post('/translate', { text: 'Hello', from: 'en', to: 'ko', domain: 'medical' });
+    assert.equal(res.status, 400);
+    assert.equal((await res.json()).error, "Translation from 'en' to 'ko' is not supported");
+});
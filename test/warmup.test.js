@@ -0,0 +1,35 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+test('warmup: true loads the pair and returns the warmed marker', async () => {
+    assert.equal((await (await server.fetch('/health')).json()).bergamotLoaded, false);
+    const res = await server.post('/translate', { from: 'en', to: 'zh', warmup: true });
+    assert.equal(res.status, 200);
+    assert.deepEqual(await res.json(), { warmed: true, from: 'en', to: 'zh' });
+    assert.match(server.output(), /Creating WASM instance for model: en-zh/);
+    assert.equal((await (await server.fetch('/health')).json()).bergamotLoaded, true);
+});
+
+test('warmup never calls the engine, even with text', async () => {
+    // The stub traps on CRASH, so a translation here would be a 502
+    const res = await server.post('/translate', { text: 'CRASH 你好', to: 'en', warmup: true });
+    assert.deepEqual(await res.json(), { warmed: true, from: 'zh', to: 'en' });
+    assert.match(server.output(), /Creating WASM instance for model: zh-en/);
+});
+
+test('warmup without text needs an explicit from', async () => {
+    const res = await server.post('/translate', { to: 'zh', warmup: true });
+    assert.equal(res.status, 400);
+    assert.match((await res.json()).error, /warmup without text requires an explicit from/);
+});
+
+test('warming an unsupported pair reports it', async () => {
+    const res = await server.post('/translate', { from: 'ko', to: 'zh', warmup: true });
+    assert.equal(res.status, 400);
+    assert.equal((await res.json()).code, 'MODEL_NOT_AVAILABLE');
+});
@@ -0,0 +1,33 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startServer } from './helpers.js';
+
+let server;
+before(async () => { server = await startServer(); });
+after(() => server.stop());
+
+const text = '\n  Hello   big\n\tworld  \n';
+
+async function translate(whitespace) {
+    const res = await server.post('/translate', { text, from: 'en', to: 'zh', ...(whitespace && { whitespace }) });
+    assert.equal(res.status, 200);
+    return (await res.json()).text;
+}
+
+test('preserve (the default) reattaches the exact leading and trailing whitespace', async () => {
+    assert.equal(await translate('preserve'), '\n  [zh] Hello   big\n\tworld  \n');
+    assert.equal(await translate(null), '\n  [zh] Hello   big\n\tworld  \n');
+});
+
+test('collapse squeezes every whitespace run to one space', async () => {
+    assert.equal(await translate('collapse'), '[zh] Hello big world');
+});
+
+test('trim strips the ends only', async () => {
+    assert.equal(await translate('trim'), '[zh] Hello   big\n\tworld');
+});
+
+test('an unknown policy is a 400', async () => {
+    const res = await server.post('/translate', { text, from: 'en', to: 'zh', whitespace: 'squash' });
+    assert.equal(res.status, 400);
+});
@@ -0,0 +1,111 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+import zlib from 'node:zlib';
+import { startServer } from './helpers.js';
+
+const CRC_TABLE = Array.from({ length: 256 }, (_, n) => {
+    let c = n;
+    for (let k = 0; k < 8; k++) c = c & 1 ? 0xedb88320 ^ (c >>> 1) : c >>> 1;
+    return c >>> 0;
+});
+
+function crc32(buffer) {
+    let crc = 0xffffffff;
+    for (const byte of buffer) crc = CRC_TABLE[(crc ^ byte) & 0xff] ^ (crc >>> 8);
+    return (crc ^ 0xffffffff) >>> 0;
+}
+
+// Build a zip archive of { name: content }; entries under a folder (as zip tools produce for a
+// zipped directory), deflated or stored alternately so both methods are read
+function createZip(files) {
+    const locals = [];
+    const centrals = [];
+    let offset = 0;
+    Object.entries(files).forEach(([name, content], i) => {
+        const data = Buffer.from(content);
+        const method = i % 2 === 0 ? 8 : 0;
+        const stored = method === 8 ? zlib.deflateRawSync(data) : data;
+        const fileName = Buffer.from(name);
+        const local = Buffer.alloc(30);
+        local.writeUInt32LE(0x04034b50, 0);
+        local.writeUInt16LE(20, 4);
+        local.writeUInt16LE(method, 8);
+        local.writeUInt32LE(crc32(data), 14);
+        local.writeUInt32LE(stored.length, 18);
+        local.writeUInt32LE(data.length, 22);
+        local.writeUInt16LE(fileName.length, 26);
+        const central = Buffer.alloc(46);
+        central.writeUInt32LE(0x02014b50, 0);
+        central.writeUInt16LE(20, 4);
+        central.writeUInt16LE(20, 6);
+        central.writeUInt16LE(method, 10);
+        central.writeUInt32LE(crc32(data), 16);
+        central.writeUInt32LE(stored.length, 20);
+        central.writeUInt32LE(data.length, 24);
+        central.writeUInt16LE(fileName.length, 28);
+        central.writeUInt32LE(offset, 42);
+        locals.push(local, fileName, stored);
+        centrals.push(central, fileName);
+        offset += local.length + fileName.length + stored.length;
+    });
+    const directory = Buffer.concat(centrals);
+    const end = Buffer.alloc(22);
+    end.writeUInt32LE(0x06054b50, 0);
+    end.writeUInt16LE(centrals.length / 2, 8);
+    end.writeUInt16LE(centrals.length / 2, 10);
+    end.writeUInt32LE(directory.length, 12);
+    end.writeUInt32LE(offset, 16);
+    return Buffer.concat([...locals, directory, end]);
+}
+
+const archive = createZip({
+    'en-zh/model.intgemm8.bin': 'stub model',
+    'en-zh/lex.s2t.bin': 'stub lex',
+    'en-zh/vocab.xxen.spm': 'stub vocab',
+});
+const archiveTime = new Date('2026-01-01T00:00:00Z');
+
+let cacheDir;
+before(async () => { cacheDir = await fs.mkdtemp(path.join(os.tmpdir(), 'linguaspark-zip-cache-')); });
+after(() => fs.rm(cacheDir, { recursive: true, force: true }));
+
+function startWithZip() {
+    return startServer({
+        pairs: [],
+        env: { ZIP_CACHE_DIR: cacheDir },
+        async prepare(modelsDir) {
+            const zipPath = path.join(modelsDir, 'en-zh.zip');
+            await fs.writeFile(zipPath, archive);
+            await fs.utimes(zipPath, archiveTime, archiveTime);
+        },
+    });
+}
+
+test('a zipped model is extracted to ZIP_CACHE_DIR and loaded', async () => {
+    const server = await startWithZip();
+    try {
+        assert.match(server.output(), /Extracting model archive: .*en-zh\.zip/);
+        assert.equal(await fs.readFile(path.join(cacheDir, 'en-zh', 'lex.s2t.bin'), 'utf8'), 'stub lex');
+        assert.equal(await fs.readFile(path.join(cacheDir, 'en-zh', 'model.intgemm8.bin'), 'utf8'), 'stub model');
+
+        const { models } = await (await server.fetch('/models')).json();
+        assert.deepEqual(models.map(m => m.key), ['en-zh']);
+        const res = await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' });
+        assert.equal((await res.json()).text, '[zh] Hello');
+    } finally {
+        await server.stop();
+    }
+});
+
+test('an unchanged zip is not extracted again', async () => {
+    const server = await startWithZip();
+    try {
+        assert.doesNotMatch(server.output(), /Extracting model archive/);
+        assert.equal((await server.post('/translate', { text: 'Hello', from: 'en', to: 'zh' })).status, 200);
+    } finally {
+        await server.stop();
+    }
+});